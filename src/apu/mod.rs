@@ -0,0 +1,268 @@
+//0xFF10 - 0xFF14  NR10-NR14  Channel 1, square with sweep
+//0xFF16 - 0xFF19  NR21-NR24  Channel 2, square
+//0xFF1A - 0xFF1E  NR30-NR34  Channel 3, wave
+//0xFF20 - 0xFF23  NR41-NR44  Channel 4, noise
+//0xFF24           NR50       Master volume / VIN panning
+//0xFF25           NR51       Sound panning
+//0xFF26           NR52       Sound on/off (bit 7) and channel status (bits 0-3, read only)
+//0xFF30 - 0xFF3F  Wave RAM
+
+pub mod envelope;
+pub mod length;
+pub mod noise;
+pub mod square;
+pub mod wave;
+
+use crate::apu::noise::Noise;
+use crate::apu::square::Square;
+use crate::apu::wave::Wave;
+
+pub const CLOCK_RATE: u32 = 4_194_304;
+
+// Bits that always read back as 1 for FF10-FF26, write only bits included
+const READ_MASKS: [u8; 0x17] = [
+    0x80, 0x3F, 0x00, 0xFF, 0xBF, // NR10-NR14
+    0xFF, 0x3F, 0x00, 0xFF, 0xBF, // unused, NR21-NR24
+    0x7F, 0xFF, 0x9F, 0xFF, 0xBF, // NR30-NR34
+    0xFF, 0xFF, 0x00, 0x00, 0xBF, // unused, NR41-NR44
+    0x00, 0x00, 0x70, // NR50-NR52
+];
+
+/// Models the capacitor on the Game Boy's output that removes the DAC's DC offset.
+struct HighPass {
+    charge_factor: f32,
+    left: f32,
+    right: f32,
+}
+
+impl HighPass {
+    fn new(sample_rate: u32) -> Self {
+        let mut high_pass = Self {
+            charge_factor: 0.0,
+            left: 0.0,
+            right: 0.0,
+        };
+        high_pass.set_sample_rate(sample_rate);
+        high_pass
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.charge_factor = 0.999958f32.powf(CLOCK_RATE as f32 / sample_rate as f32);
+    }
+
+    fn filter(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let out_left = left - self.left;
+        let out_right = right - self.right;
+
+        self.left = left - out_left * self.charge_factor;
+        self.right = right - out_right * self.charge_factor;
+
+        (out_left, out_right)
+    }
+}
+
+pub struct Apu {
+    enabled: bool,
+
+    square1: Square,
+    square2: Square,
+    wave: Wave,
+    noise: Noise,
+
+    registers: [u8; 0x17],
+
+    // 512 Hz frame sequencer step (0-7), clocked from DIV
+    frame_step: u8,
+
+    sample_rate: u32,
+    sample_counter: u32,
+    high_pass: HighPass,
+
+    // Interleaved stereo samples waiting to be drained by the frontend
+    samples: Vec<f32>,
+}
+
+impl Apu {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            enabled: false,
+            square1: Square::new(true),
+            square2: Square::new(false),
+            wave: Wave::new(),
+            noise: Noise::new(),
+            registers: [0; 0x17],
+            frame_step: 0,
+            sample_rate,
+            sample_counter: 0,
+            high_pass: HighPass::new(sample_rate),
+            samples: Vec::new(),
+        }
+    }
+
+    /// Change the output rate. Can be called every frame for rate control
+    /// without disturbing the resampler or filter state.
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate;
+        self.high_pass.set_sample_rate(sample_rate);
+    }
+
+    /// Take all samples generated since the last call, as interleaved left/right pairs.
+    pub fn drain_samples(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.samples)
+    }
+
+    pub fn read_register(&self, address: u16) -> u8 {
+        match address {
+            0xFF26 => {
+                let status = (self.square1.enabled as u8)
+                    | (self.square2.enabled as u8) << 1
+                    | (self.wave.enabled as u8) << 2
+                    | (self.noise.enabled as u8) << 3;
+
+                0x70 | (self.enabled as u8) << 7 | status
+            }
+            0xFF10..=0xFF25 => {
+                let index = (address - 0xFF10) as usize;
+                self.registers[index] | READ_MASKS[index]
+            }
+            0xFF30..=0xFF3F => self.wave.ram[(address - 0xFF30) as usize],
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0xFF26 => self.set_power(value & 0x80 != 0),
+            0xFF30..=0xFF3F => self.wave.ram[(address - 0xFF30) as usize] = value,
+            // Registers can't be written while the APU is off
+            _ if !self.enabled => (),
+            0xFF10..=0xFF25 => {
+                self.registers[(address - 0xFF10) as usize] = value;
+
+                match address {
+                    0xFF10..=0xFF14 => self.square1.write(address - 0xFF10, value),
+                    0xFF15..=0xFF19 => self.square2.write(address - 0xFF15, value),
+                    0xFF1A..=0xFF1E => self.wave.write(address - 0xFF1A, value),
+                    0xFF1F..=0xFF23 => self.noise.write(address - 0xFF1F, value),
+                    _ => (),
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn set_power(&mut self, on: bool) {
+        if on && !self.enabled {
+            self.frame_step = 0;
+        } else if !on && self.enabled {
+            // Powering off clears every register except wave RAM
+            let wave_ram = self.wave.ram;
+
+            self.square1 = Square::new(true);
+            self.square2 = Square::new(false);
+            self.wave = Wave::new();
+            self.wave.ram = wave_ram;
+            self.noise = Noise::new();
+            self.registers = [0; 0x17];
+        }
+        self.enabled = on;
+    }
+
+    /// Advance by `cycles` T-cycles. `frame_sequencer_clocks` is the number of
+    /// DIV-APU events (falling edges of DIV bit 4) that happened in that time.
+    pub fn tick(&mut self, cycles: u32, frame_sequencer_clocks: u32) {
+        if self.enabled {
+            for _ in 0..frame_sequencer_clocks {
+                self.clock_frame_sequencer();
+            }
+        }
+
+        for _ in 0..cycles {
+            if self.enabled {
+                self.square1.step();
+                self.square2.step();
+                self.wave.step();
+                self.noise.step();
+            }
+
+            self.sample_counter += self.sample_rate;
+            if self.sample_counter >= CLOCK_RATE {
+                self.sample_counter -= CLOCK_RATE;
+
+                let (left, right) = self.mix();
+                let (left, right) = self.high_pass.filter(left, right);
+                self.samples.push(left);
+                self.samples.push(right);
+            }
+        }
+    }
+
+    // Step  Length  Envelope  Sweep
+    //  0    clock
+    //  2    clock             clock
+    //  4    clock
+    //  6    clock             clock
+    //  7            clock
+    fn clock_frame_sequencer(&mut self) {
+        if self.frame_step.is_multiple_of(2) {
+            self.square1.clock_length();
+            self.square2.clock_length();
+            self.wave.clock_length();
+            self.noise.clock_length();
+        }
+
+        if self.frame_step == 2 || self.frame_step == 6 {
+            self.square1.clock_sweep();
+        }
+
+        if self.frame_step == 7 {
+            self.square1.clock_envelope();
+            self.square2.clock_envelope();
+            self.noise.clock_envelope();
+        }
+
+        self.frame_step = (self.frame_step + 1) % 8;
+    }
+
+    fn mix(&self) -> (f32, f32) {
+        if !self.enabled {
+            return (0.0, 0.0);
+        }
+
+        let channels = [
+            (self.square1.output(), self.square1.dac_enabled()),
+            (self.square2.output(), self.square2.dac_enabled()),
+            (self.wave.output(), self.wave.dac_enabled()),
+            (self.noise.output(), self.noise.dac_enabled()),
+        ];
+
+        let panning = self.registers[0x15];
+        let mut left = 0.0;
+        let mut right = 0.0;
+
+        for (i, &(output, dac_enabled)) in channels.iter().enumerate() {
+            if !dac_enabled {
+                continue;
+            }
+
+            // Each DAC maps 0-15 linearly onto -1.0..1.0
+            let analog = output as f32 / 7.5 - 1.0;
+
+            if panning & (0x10 << i) != 0 {
+                left += analog;
+            }
+            if panning & (0x01 << i) != 0 {
+                right += analog;
+            }
+        }
+
+        let volume = self.registers[0x14];
+        let left_volume = (((volume >> 4) & 0x07) + 1) as f32 / 8.0;
+        let right_volume = ((volume & 0x07) + 1) as f32 / 8.0;
+
+        (left / 4.0 * left_volume, right / 4.0 * right_volume)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,110 @@
+use crate::apu::{Apu, CLOCK_RATE};
+
+fn setup_apu() -> Apu {
+    let mut apu = Apu::new(48000);
+    apu.write_register(0xFF26, 0x80);
+    apu.write_register(0xFF24, 0x77);
+    apu.write_register(0xFF25, 0xFF);
+    apu
+}
+
+#[test]
+fn power_off_clears_and_locks_registers() {
+    let mut apu = setup_apu();
+    apu.write_register(0xFF30, 0x12);
+
+    apu.write_register(0xFF26, 0x00);
+    apu.write_register(0xFF24, 0x77);
+
+    assert_eq!(apu.read_register(0xFF26), 0x70);
+    assert_eq!(apu.read_register(0xFF24), 0x00);
+    assert_eq!(apu.read_register(0xFF30), 0x12);
+}
+
+#[test]
+fn registers_read_back_with_unused_bits_set() {
+    let mut apu = setup_apu();
+
+    apu.write_register(0xFF11, 0x80);
+    apu.write_register(0xFF13, 0x12);
+    apu.write_register(0xFF1A, 0x00);
+
+    assert_eq!(apu.read_register(0xFF11), 0xBF);
+    assert_eq!(apu.read_register(0xFF13), 0xFF);
+    assert_eq!(apu.read_register(0xFF1A), 0x7F);
+    assert_eq!(apu.read_register(0xFF15), 0xFF);
+}
+
+#[test]
+fn trigger_enables_channel_status() {
+    let mut apu = setup_apu();
+
+    apu.write_register(0xFF12, 0xF0);
+    apu.write_register(0xFF14, 0x80);
+    apu.write_register(0xFF21, 0xF0);
+    apu.write_register(0xFF23, 0x80);
+
+    assert_eq!(apu.read_register(0xFF26), 0xF9);
+}
+
+#[test]
+fn trigger_without_dac_stays_off() {
+    let mut apu = setup_apu();
+
+    apu.write_register(0xFF17, 0x00);
+    apu.write_register(0xFF19, 0x80);
+
+    assert_eq!(apu.read_register(0xFF26) & 0x02, 0);
+}
+
+#[test]
+fn length_counter_disables_channel() {
+    let mut apu = setup_apu();
+    apu.write_register(0xFF12, 0xF0);
+    apu.write_register(0xFF11, 0x3E); // 2 length clocks
+    apu.write_register(0xFF14, 0xC0);
+
+    // Length is clocked on every other frame sequencer step
+    apu.tick(0, 1);
+    assert_eq!(apu.read_register(0xFF26) & 0x01, 0x01);
+
+    apu.tick(0, 2);
+    assert_eq!(apu.read_register(0xFF26) & 0x01, 0x00);
+}
+
+#[test]
+fn sweep_overflow_disables_channel() {
+    let mut apu = setup_apu();
+    apu.write_register(0xFF12, 0xF0);
+    apu.write_register(0xFF10, 0x11);
+    apu.write_register(0xFF13, 0xFF);
+    apu.write_register(0xFF14, 0x87);
+
+    // Frequency 0x7FF + (0x7FF >> 1) overflows on the trigger check
+    assert_eq!(apu.read_register(0xFF26) & 0x01, 0x00);
+}
+
+#[test]
+fn generates_stereo_samples_at_output_rate() {
+    let mut apu = setup_apu();
+
+    apu.tick(CLOCK_RATE / 64, 0);
+
+    assert_eq!(apu.drain_samples().len(), 48000 / 64 * 2);
+    assert!(apu.drain_samples().is_empty());
+}
+
+#[test]
+fn square_wave_reaches_output() {
+    let mut apu = setup_apu();
+    apu.write_register(0xFF16, 0x80);
+    apu.write_register(0xFF17, 0xF0);
+    apu.write_register(0xFF18, 0x00);
+    apu.write_register(0xFF19, 0x87);
+
+    apu.tick(CLOCK_RATE / 64, 0);
+
+    let samples = apu.drain_samples();
+    let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+    assert!(peak > 0.1);
+}
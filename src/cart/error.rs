@@ -0,0 +1,74 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum CartError {
+    NotFound(PathBuf),
+    Io(io::Error),
+    /// Shorter than the 0x150 bytes needed for a header
+    TooSmall(usize),
+    UnsupportedMapper(u8),
+    /// ROM length doesn't match the header's ROM size code
+    SizeMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    /// Header checksum at 0x014D is wrong, a real boot ROM would lock up
+    BadChecksum {
+        expected: u8,
+        computed: u8,
+    },
+    /// Existing `.sav` doesn't fit this cartridge's RAM
+    InvalidSave {
+        path: PathBuf,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl fmt::Display for CartError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CartError::NotFound(path) => write!(f, "ROM file {} not found", path.display()),
+            CartError::Io(e) => write!(f, "failed to read cartridge: {}", e),
+            CartError::TooSmall(size) => write!(
+                f,
+                "ROM is only {} bytes, too small to hold a cartridge header",
+                size
+            ),
+            CartError::UnsupportedMapper(code) => {
+                write!(f, "cartridge type {:02X} is not supported", code)
+            }
+            CartError::SizeMismatch { expected, actual } => write!(
+                f,
+                "ROM is {} bytes but its header says {} bytes, the file may be truncated",
+                actual, expected
+            ),
+            CartError::BadChecksum { expected, computed } => write!(
+                f,
+                "header checksum is {:02X}, computed {:02X}",
+                expected, computed
+            ),
+            CartError::InvalidSave {
+                path,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "{} is {} bytes, expected {} for this cartridge",
+                path.display(),
+                actual,
+                expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CartError {}
+
+impl From<io::Error> for CartError {
+    fn from(e: io::Error) -> Self {
+        CartError::Io(e)
+    }
+}
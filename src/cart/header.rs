@@ -0,0 +1,314 @@
+//0x0100 - 0x0103  Entry point
+//0x0104 - 0x0133  Nintendo logo
+//0x0134 - 0x0143  Title (0x013F - 0x0142 manufacturer code and 0x0143 CGB flag on newer carts)
+//0x0144 - 0x0145  New licensee code
+//0x0146           SGB flag
+//0x0147           Cartridge type
+//0x0148           ROM size
+//0x0149           RAM size
+//0x014A           Destination code
+//0x014B           Old licensee code (0x33 means use the new licensee code)
+//0x014C           Mask ROM version
+//0x014D           Header checksum over 0x0134 - 0x014C
+//0x014E - 0x014F  Global checksum over the whole ROM except these two bytes
+
+use crate::cart::error::CartError;
+use crate::cart::Cart;
+
+pub const HEADER_END: usize = 0x150;
+
+const NINTENDO_LOGO: [u8; 48] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CgbSupport {
+    None,
+    /// Runs on DMG, with extra features on CGB
+    Enhanced,
+    Only,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SgbSupport {
+    None,
+    Supported,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MapperKind {
+    RomOnly,
+    Mbc1,
+    Mbc2,
+    Mmm01,
+    Mbc3,
+    Mbc5,
+    Unknown(u8),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CartType {
+    pub mapper: MapperKind,
+    pub ram: bool,
+    pub battery: bool,
+    pub timer: bool,
+    pub rumble: bool,
+}
+
+impl CartType {
+    pub fn from_code(code: u8) -> Self {
+        let (mapper, ram, battery, timer, rumble) = match code {
+            0x00 => (MapperKind::RomOnly, false, false, false, false),
+            0x01 => (MapperKind::Mbc1, false, false, false, false),
+            0x02 => (MapperKind::Mbc1, true, false, false, false),
+            0x03 => (MapperKind::Mbc1, true, true, false, false),
+            // MBC2's RAM is built in
+            0x05 => (MapperKind::Mbc2, true, false, false, false),
+            0x06 => (MapperKind::Mbc2, true, true, false, false),
+            0x08 => (MapperKind::RomOnly, true, false, false, false),
+            0x09 => (MapperKind::RomOnly, true, true, false, false),
+            0x0B => (MapperKind::Mmm01, false, false, false, false),
+            0x0C => (MapperKind::Mmm01, true, false, false, false),
+            0x0D => (MapperKind::Mmm01, true, true, false, false),
+            0x0F => (MapperKind::Mbc3, false, true, true, false),
+            0x10 => (MapperKind::Mbc3, true, true, true, false),
+            0x11 => (MapperKind::Mbc3, false, false, false, false),
+            0x12 => (MapperKind::Mbc3, true, false, false, false),
+            0x13 => (MapperKind::Mbc3, true, true, false, false),
+            0x19 => (MapperKind::Mbc5, false, false, false, false),
+            0x1A => (MapperKind::Mbc5, true, false, false, false),
+            0x1B => (MapperKind::Mbc5, true, true, false, false),
+            0x1C => (MapperKind::Mbc5, false, false, false, true),
+            0x1D => (MapperKind::Mbc5, true, false, false, true),
+            0x1E => (MapperKind::Mbc5, true, true, false, true),
+            other => (MapperKind::Unknown(other), false, false, false, false),
+        };
+
+        Self {
+            mapper,
+            ram,
+            battery,
+            timer,
+            rumble,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RomHeader {
+    pub entry: [u8; 4],
+    pub logo: [u8; 48],
+    pub title: String,
+    pub cgb_support: CgbSupport,
+    pub new_lic_code: String,
+    pub sgb_support: SgbSupport,
+    pub type_val: u8,
+    pub cart_type: CartType,
+    pub rom_size: u8,
+    pub ram_size: u8,
+    pub ram_size_bytes: usize,
+    pub dest_code: u8,
+    pub lic_code: u8,
+    pub version: u8,
+    pub checksum: u8,
+    pub global_checksum: u16,
+
+    // Checksums worked out from the ROM contents
+    pub computed_checksum: u8,
+    pub computed_global_checksum: u16,
+}
+
+impl RomHeader {
+    pub fn parse(rom: &[u8]) -> Result<RomHeader, CartError> {
+        if rom.len() < HEADER_END {
+            return Err(CartError::TooSmall(rom.len()));
+        }
+
+        let cgb_support = match rom[0x143] {
+            0x80 => CgbSupport::Enhanced,
+            0xC0 => CgbSupport::Only,
+            _ => CgbSupport::None,
+        };
+
+        // The last title byte became the CGB flag
+        let title_end = match cgb_support {
+            CgbSupport::None => 0x144,
+            _ => 0x143,
+        };
+        let title = String::from_utf8_lossy(&rom[0x134..title_end])
+            .trim_end_matches('\0')
+            .to_string();
+
+        let sgb_support = match rom[0x146] {
+            0x03 => SgbSupport::Supported,
+            _ => SgbSupport::None,
+        };
+
+        Ok(RomHeader {
+            entry: rom[0x100..0x104].try_into().unwrap(),
+            logo: rom[0x104..0x134].try_into().unwrap(),
+            title,
+            cgb_support,
+            new_lic_code: String::from_utf8_lossy(&rom[0x144..0x146]).to_string(),
+            sgb_support,
+            type_val: rom[0x147],
+            cart_type: CartType::from_code(rom[0x147]),
+            rom_size: rom[0x148],
+            ram_size: rom[0x149],
+            ram_size_bytes: ram_size_bytes(rom[0x149]),
+            dest_code: rom[0x14A],
+            lic_code: rom[0x14B],
+            version: rom[0x14C],
+            checksum: rom[0x14D],
+            global_checksum: u16::from_be_bytes([rom[0x14E], rom[0x14F]]),
+            computed_checksum: header_checksum(rom),
+            computed_global_checksum: global_checksum(rom),
+        })
+    }
+
+    /// ROM size in bytes from the header's size code, None for the rare unofficial codes.
+    pub fn rom_size_bytes(&self) -> Option<usize> {
+        match self.rom_size {
+            0x00..=0x08 => Some(0x8000 << self.rom_size),
+            _ => None,
+        }
+    }
+
+    /// The check the boot ROM does before starting the cartridge.
+    pub fn verify_checksum(&self) -> Result<(), CartError> {
+        if self.checksum != self.computed_checksum {
+            return Err(CartError::BadChecksum {
+                expected: self.checksum,
+                computed: self.computed_checksum,
+            });
+        }
+        Ok(())
+    }
+
+    /// Publisher name, from the new licensee table when the old code says to look there.
+    pub fn licensee(&self) -> &'static str {
+        match self.lic_code {
+            0x33 => new_license_name(&self.new_lic_code),
+            code => Cart::license_name(code),
+        }
+    }
+
+    /// Problems with the header that don't stop the cartridge from running.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if self.logo != NINTENDO_LOGO {
+            warnings.push("Nintendo logo doesn't match, a real Game Boy would lock up".to_string());
+        }
+        if let Err(e) = self.verify_checksum() {
+            warnings.push(e.to_string());
+        }
+        if self.global_checksum != self.computed_global_checksum {
+            warnings.push(format!(
+                "global checksum is {:04X}, computed {:04X}",
+                self.global_checksum, self.computed_global_checksum
+            ));
+        }
+        if let MapperKind::Unknown(code) = self.cart_type.mapper {
+            warnings.push(format!("unknown cartridge type {:02X}", code));
+        }
+
+        warnings
+    }
+}
+
+/// The boot ROM refuses to start a cartridge when this doesn't match byte 0x014D.
+pub fn header_checksum(rom: &[u8]) -> u8 {
+    rom[0x134..=0x14C]
+        .iter()
+        .fold(0u8, |sum, &byte| sum.wrapping_sub(byte).wrapping_sub(1))
+}
+
+/// Never checked by hardware.
+pub fn global_checksum(rom: &[u8]) -> u16 {
+    rom.iter()
+        .enumerate()
+        .filter(|&(address, _)| address != 0x14E && address != 0x14F)
+        .fold(0u16, |sum, (_, &byte)| sum.wrapping_add(byte as u16))
+}
+
+fn ram_size_bytes(code: u8) -> usize {
+    match code {
+        0x01 => 0x800,
+        0x02 => 0x2000,
+        0x03 => 0x8000,
+        0x04 => 0x20000,
+        0x05 => 0x10000,
+        _ => 0,
+    }
+}
+
+fn new_license_name(code: &str) -> &'static str {
+    match code {
+        "00" => "None",
+        "01" => "Nintendo R&D1",
+        "08" => "Capcom",
+        "13" => "Electronic Arts",
+        "18" => "Hudson Soft",
+        "19" => "b-ai",
+        "20" => "kss",
+        "22" => "pow",
+        "24" => "PCM Complete",
+        "25" => "san-x",
+        "28" => "Kemco Japan",
+        "29" => "seta",
+        "30" => "Viacom",
+        "31" => "Nintendo",
+        "32" => "Bandai",
+        "33" => "Ocean/Acclaim",
+        "34" => "Konami",
+        "35" => "Hector",
+        "37" => "Taito",
+        "38" => "Hudson",
+        "39" => "Banpresto",
+        "41" => "Ubi Soft",
+        "42" => "Atlus",
+        "44" => "Malibu",
+        "46" => "angel",
+        "47" => "Bullet-Proof",
+        "49" => "irem",
+        "50" => "Absolute",
+        "51" => "Acclaim",
+        "52" => "Activision",
+        "53" => "American sammy",
+        "54" => "Konami",
+        "55" => "Hi tech entertainment",
+        "56" => "LJN",
+        "57" => "Matchbox",
+        "58" => "Mattel",
+        "59" => "Milton Bradley",
+        "60" => "Titus",
+        "61" => "Virgin",
+        "64" => "LucasArts",
+        "67" => "Ocean",
+        "69" => "Electronic Arts",
+        "70" => "Infogrames",
+        "71" => "Interplay",
+        "72" => "Broderbund",
+        "73" => "sculptured",
+        "75" => "sci",
+        "78" => "THQ",
+        "79" => "Accolade",
+        "80" => "misawa",
+        "83" => "lozc",
+        "86" => "Tokuma Shoten Intermedia",
+        "87" => "Tsukuda Original",
+        "91" => "Chunsoft",
+        "92" => "Video system",
+        "93" => "Ocean/Acclaim",
+        "95" => "Varie",
+        "96" => "Yonezawa/s'pal",
+        "97" => "Kaneko",
+        "99" => "Pack in soft",
+        "9H" => "Bottom Up",
+        "A4" => "Konami (Yu-Gi-Oh!)",
+        _ => "Unknown",
+    }
+}
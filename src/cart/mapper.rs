@@ -0,0 +1,84 @@
+use crate::cart::error::CartError;
+use crate::cart::header::{MapperKind, RomHeader};
+use crate::cart::mbc1::Mbc1;
+use crate::cart::mbc2::Mbc2;
+use crate::cart::mbc3::Mbc3;
+use crate::cart::mbc5::Mbc5;
+use crate::cart::rom_only::RomOnly;
+use crate::cart::rtc::{SystemClock, FOOTER_SIZE};
+
+pub const ROM_BANK_SIZE: usize = 0x4000;
+pub const RAM_BANK_SIZE: usize = 0x2000;
+
+/// Memory bank controller on the cartridge, sees every access to 0x0000-0x7FFF and 0xA000-0xBFFF.
+pub trait Mapper {
+    fn read_rom(&self, address: u16) -> u8;
+
+    /// Writes to the ROM area set the controller's registers.
+    fn write_register(&mut self, address: u16, value: u8);
+
+    fn read_ram(&self, address: u16) -> u8;
+
+    fn write_ram(&mut self, address: u16, value: u8);
+
+    /// RAM kept alive by the cartridge battery, None if there is no battery.
+    fn battery_ram(&self) -> Option<&[u8]> {
+        None
+    }
+
+    /// Restore battery RAM saved by an earlier session.
+    fn load_battery_ram(&mut self, _data: &[u8]) {}
+
+    /// Real-time clock state to append to the save, for cartridges with a clock.
+    fn rtc_footer(&self) -> Option<[u8; FOOTER_SIZE]> {
+        None
+    }
+
+    fn load_rtc_footer(&mut self, _footer: &[u8]) {}
+
+    /// New rumble motor state if it changed since the last call.
+    fn take_rumble_event(&mut self) -> Option<bool> {
+        None
+    }
+}
+
+/// Pick the mapper for the cartridge type in the header.
+pub fn from_header(header: &RomHeader, rom: Vec<u8>) -> Result<Box<dyn Mapper>, CartError> {
+    let cart_type = header.cart_type;
+    let ram = match cart_type.ram {
+        true => vec![0; header.ram_size_bytes],
+        false => Vec::new(),
+    };
+    let battery = cart_type.battery;
+
+    match cart_type.mapper {
+        MapperKind::RomOnly => Ok(Box::new(RomOnly::new(rom, ram, battery))),
+        MapperKind::Mbc1 => Ok(Box::new(Mbc1::new(rom, ram, battery))),
+        MapperKind::Mbc2 => Ok(Box::new(Mbc2::new(rom, battery))),
+        MapperKind::Mbc3 => Ok(Box::new(Mbc3::new(
+            rom,
+            ram,
+            battery,
+            cart_type.timer,
+            Box::new(SystemClock),
+        ))),
+        MapperKind::Mbc5 => Ok(Box::new(Mbc5::new(rom, ram, battery, cart_type.rumble))),
+        _ => Err(CartError::UnsupportedMapper(header.type_val)),
+    }
+}
+
+/// Byte at `address` within 16 KiB ROM bank `bank`, wrapping banks past the end of the ROM.
+pub fn rom_bank_byte(rom: &[u8], bank: usize, address: u16) -> u8 {
+    if rom.is_empty() {
+        return 0xFF;
+    }
+    rom[(bank * ROM_BANK_SIZE + (address as usize & 0x3FFF)) % rom.len()]
+}
+
+/// Offset into external RAM for `address` within 8 KiB bank `bank`, None if there is no RAM.
+pub fn ram_offset(ram: &[u8], bank: usize, address: u16) -> Option<usize> {
+    if ram.is_empty() {
+        return None;
+    }
+    Some((bank * RAM_BANK_SIZE + (address as usize & 0x1FFF)) % ram.len())
+}
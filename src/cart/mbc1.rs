@@ -0,0 +1,89 @@
+//0x0000 - 0x1FFF  RAM enable (0x0A in the low nibble enables)
+//0x2000 - 0x3FFF  ROM bank, lower 5 bits (0 is treated as 1)
+//0x4000 - 0x5FFF  RAM bank, or upper 2 bits of the ROM bank
+//0x6000 - 0x7FFF  Banking mode select
+
+use crate::cart::mapper::{ram_offset, rom_bank_byte, Mapper};
+
+pub struct Mbc1 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    has_battery: bool,
+    ram_enabled: bool,
+    bank1: u8,
+    bank2: u8,
+
+    // In mode 1 bank2 also switches 0x0000-0x3FFF and the RAM bank
+    advanced_mode: bool,
+}
+
+impl Mbc1 {
+    pub fn new(rom: Vec<u8>, ram: Vec<u8>, has_battery: bool) -> Self {
+        Self {
+            rom,
+            ram,
+            has_battery,
+            ram_enabled: false,
+            bank1: 1,
+            bank2: 0,
+            advanced_mode: false,
+        }
+    }
+
+    fn ram_bank(&self) -> usize {
+        if self.advanced_mode {
+            self.bank2 as usize
+        } else {
+            0
+        }
+    }
+}
+
+impl Mapper for Mbc1 {
+    fn read_rom(&self, address: u16) -> u8 {
+        let bank = match address {
+            0x0000..=0x3FFF if self.advanced_mode => (self.bank2 as usize) << 5,
+            0x0000..=0x3FFF => 0,
+            _ => (self.bank2 as usize) << 5 | self.bank1 as usize,
+        };
+
+        rom_bank_byte(&self.rom, bank, address)
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => self.bank1 = (value & 0x1F).max(1),
+            0x4000..=0x5FFF => self.bank2 = value & 0x03,
+            _ => self.advanced_mode = value & 0x01 != 0,
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+
+        match ram_offset(&self.ram, self.ram_bank(), address) {
+            Some(offset) => self.ram[offset],
+            None => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        if !self.ram_enabled {
+            return;
+        }
+
+        if let Some(offset) = ram_offset(&self.ram, self.ram_bank(), address) {
+            self.ram[offset] = value;
+        }
+    }
+    fn battery_ram(&self) -> Option<&[u8]> {
+        self.has_battery.then_some(&self.ram[..])
+    }
+
+    fn load_battery_ram(&mut self, data: &[u8]) {
+        self.ram.copy_from_slice(data);
+    }
+}
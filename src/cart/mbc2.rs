@@ -0,0 +1,72 @@
+//0x0000 - 0x3FFF  Address bit 8 clear: RAM enable (0x0A in the low nibble enables)
+//                 Address bit 8 set:   ROM bank, 4 bits (0 is treated as 1)
+//0xA000 - 0xA1FF  512 x 4-bit built-in RAM, echoed up to 0xBFFF
+
+use crate::cart::mapper::{rom_bank_byte, Mapper};
+
+const RAM_SIZE: usize = 512;
+
+pub struct Mbc2 {
+    rom: Vec<u8>,
+
+    // Only the low nibble of each byte is stored
+    ram: [u8; RAM_SIZE],
+    ram_enabled: bool,
+    rom_bank: u8,
+    has_battery: bool,
+}
+
+impl Mbc2 {
+    pub fn new(rom: Vec<u8>, has_battery: bool) -> Self {
+        Self {
+            rom,
+            ram: [0; RAM_SIZE],
+            ram_enabled: false,
+            rom_bank: 1,
+            has_battery,
+        }
+    }
+}
+
+impl Mapper for Mbc2 {
+    fn read_rom(&self, address: u16) -> u8 {
+        let bank = match address {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        };
+
+        rom_bank_byte(&self.rom, bank, address)
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x3FFF if address & 0x0100 == 0 => self.ram_enabled = value & 0x0F == 0x0A,
+            0x0000..=0x3FFF => self.rom_bank = (value & 0x0F).max(1),
+            _ => (),
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+        // The upper nibble isn't connected and reads as 1s
+        0xF0 | self.ram[address as usize & 0x1FF]
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        if self.ram_enabled {
+            self.ram[address as usize & 0x1FF] = value & 0x0F;
+        }
+    }
+
+    fn battery_ram(&self) -> Option<&[u8]> {
+        self.has_battery.then_some(&self.ram[..])
+    }
+
+    fn load_battery_ram(&mut self, data: &[u8]) {
+        for (cell, &value) in self.ram.iter_mut().zip(data) {
+            *cell = value & 0x0F;
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//0x0000 - 0x1FFF  RAM and RTC enable (0x0A in the low nibble enables)
+//0x2000 - 0x3FFF  ROM bank, 7 bits (0 is treated as 1)
+//0x4000 - 0x5FFF  RAM bank 0x00-0x03, or RTC register 0x08-0x0C
+//0x6000 - 0x7FFF  Latch clock data, writing 0x00 then 0x01 latches the RTC
+
+use crate::cart::mapper::{ram_offset, rom_bank_byte, Mapper};
+use crate::cart::rtc::{Rtc, TimeSource, FOOTER_SIZE};
+
+pub struct Mbc3 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    has_battery: bool,
+    ram_enabled: bool,
+    rom_bank: u8,
+
+    // RAM bank, or RTC register when 0x08-0x0C
+    ram_select: u8,
+
+    latch_armed: bool,
+    has_rtc: bool,
+    rtc: Rtc,
+    clock: Box<dyn TimeSource>,
+}
+
+impl Mbc3 {
+    pub fn new(
+        rom: Vec<u8>,
+        ram: Vec<u8>,
+        has_battery: bool,
+        has_rtc: bool,
+        clock: Box<dyn TimeSource>,
+    ) -> Self {
+        Self {
+            rom,
+            ram,
+            has_battery,
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_select: 0,
+            latch_armed: false,
+            has_rtc,
+            rtc: Rtc::new(clock.now()),
+            clock,
+        }
+    }
+}
+
+impl Mapper for Mbc3 {
+    fn read_rom(&self, address: u16) -> u8 {
+        let bank = match address {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        };
+
+        rom_bank_byte(&self.rom, bank, address)
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => self.rom_bank = (value & 0x7F).max(1),
+            0x4000..=0x5FFF => self.ram_select = value & 0x0F,
+            _ => {
+                if self.latch_armed && value == 0x01 {
+                    self.rtc.latch(self.clock.now());
+                }
+                self.latch_armed = value == 0x00;
+            }
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+
+        match self.ram_select {
+            0x00..=0x03 => match ram_offset(&self.ram, self.ram_select as usize, address) {
+                Some(offset) => self.ram[offset],
+                None => 0xFF,
+            },
+            select if self.has_rtc => self.rtc.read(select),
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        if !self.ram_enabled {
+            return;
+        }
+
+        match self.ram_select {
+            0x00..=0x03 => {
+                if let Some(offset) = ram_offset(&self.ram, self.ram_select as usize, address) {
+                    self.ram[offset] = value;
+                }
+            }
+            select if self.has_rtc => self.rtc.write(select, value, self.clock.now()),
+            _ => (),
+        }
+    }
+
+    fn battery_ram(&self) -> Option<&[u8]> {
+        self.has_battery.then_some(&self.ram[..])
+    }
+
+    fn load_battery_ram(&mut self, data: &[u8]) {
+        self.ram.copy_from_slice(data);
+    }
+
+    fn rtc_footer(&self) -> Option<[u8; FOOTER_SIZE]> {
+        (self.has_battery && self.has_rtc).then(|| self.rtc.to_footer(self.clock.now()))
+    }
+
+    fn load_rtc_footer(&mut self, footer: &[u8]) {
+        self.rtc.load_footer(footer);
+    }
+}
@@ -0,0 +1,106 @@
+//0x0000 - 0x1FFF  RAM enable (0x0A enables)
+//0x2000 - 0x2FFF  ROM bank, lower 8 bits (bank 0 can be mapped)
+//0x3000 - 0x3FFF  ROM bank, bit 8
+//0x4000 - 0x5FFF  RAM bank 0x00-0x0F, on rumble carts bit 3 drives the motor instead
+
+use crate::cart::mapper::{ram_offset, rom_bank_byte, Mapper};
+
+pub struct Mbc5 {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    has_battery: bool,
+    ram_enabled: bool,
+    rom_bank: u16,
+    ram_bank: u8,
+
+    has_rumble: bool,
+    motor_on: bool,
+
+    // Motor state changed since the frontend last looked
+    rumble_changed: bool,
+}
+
+impl Mbc5 {
+    pub fn new(rom: Vec<u8>, ram: Vec<u8>, has_battery: bool, has_rumble: bool) -> Self {
+        Self {
+            rom,
+            ram,
+            has_battery,
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            has_rumble,
+            motor_on: false,
+            rumble_changed: false,
+        }
+    }
+}
+
+impl Mapper for Mbc5 {
+    fn read_rom(&self, address: u16) -> u8 {
+        let bank = match address {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        };
+
+        rom_bank_byte(&self.rom, bank, address)
+    }
+
+    fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0x0000..=0x1FFF => self.ram_enabled = value == 0x0A,
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | value as u16,
+            0x3000..=0x3FFF => {
+                self.rom_bank = (self.rom_bank & 0xFF) | ((value & 0x01) as u16) << 8
+            }
+            0x4000..=0x5FFF if self.has_rumble => {
+                self.ram_bank = value & 0x07;
+
+                let motor_on = value & 0x08 != 0;
+                if motor_on != self.motor_on {
+                    self.motor_on = motor_on;
+                    self.rumble_changed = true;
+                }
+            }
+            0x4000..=0x5FFF => self.ram_bank = value & 0x0F,
+            _ => (),
+        }
+    }
+
+    fn read_ram(&self, address: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+
+        match ram_offset(&self.ram, self.ram_bank as usize, address) {
+            Some(offset) => self.ram[offset],
+            None => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        if !self.ram_enabled {
+            return;
+        }
+
+        if let Some(offset) = ram_offset(&self.ram, self.ram_bank as usize, address) {
+            self.ram[offset] = value;
+        }
+    }
+
+    fn battery_ram(&self) -> Option<&[u8]> {
+        self.has_battery.then_some(&self.ram[..])
+    }
+
+    fn load_battery_ram(&mut self, data: &[u8]) {
+        self.ram.copy_from_slice(data);
+    }
+
+    fn take_rumble_event(&mut self) -> Option<bool> {
+        if std::mem::take(&mut self.rumble_changed) {
+            Some(self.motor_on)
+        } else {
+            None
+        }
+    }
+}
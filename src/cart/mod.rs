@@ -1,37 +1,95 @@
-use std::fs;
-use std::io::{Error, ErrorKind, Result};
-
-#[derive(Debug, Clone)]
-pub struct RomHeader {
-    //commented out values are values I am not currently implementing
-    //pub entry: String,
-    //pub logo: String,
-    pub title: String,
-    //pub new_lic_code: u16,
-    //pub sgb_flag: u8,
-    pub type_val: u8,
-    pub rom_size: u8,
-    pub ram_size: u8,
-    pub dest_code: u8,
-    pub lic_code: u8,
-    pub version: u8,
-    pub checksum: u8,
-    pub global_checksum: u16,
-}
+pub mod error;
+pub mod header;
+pub mod mapper;
+pub mod mbc1;
+pub mod mbc2;
+pub mod mbc3;
+pub mod mbc5;
+pub mod rom_only;
+pub mod rtc;
+
+use std::fs::{self, File};
+use std::io::{self, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::cart::error::CartError;
+use crate::cart::header::RomHeader;
+use crate::cart::mapper::Mapper;
+use crate::cart::rtc::{same_clock, FOOTER_SIZE, SHORT_FOOTER_SIZE};
 
 pub struct Cart {
-    pub filename: String, //[char; 1024],
-    pub rom_data: Vec<u8>,
-    pub rom_head: Option<RomHeader>, //rc<rom_header>
+    // Empty for carts loaded from memory, which are never saved
+    pub filename: String,
+    pub rom_head: RomHeader,
+    mapper: Box<dyn Mapper>,
+
+    // Battery RAM as last written to disk, to skip saves when nothing changed
+    saved_ram: Vec<u8>,
 }
 
 impl Cart {
-    pub fn new() -> Cart {
-        Cart {
-            filename: "none".to_string(),
-            rom_data: Vec::new(),
-            rom_head: None,
+    /// Load a ROM file, along with its battery save if there is one.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Cart, CartError> {
+        let path = path.as_ref();
+        let rom = fs::read(path).map_err(|e| match e.kind() {
+            ErrorKind::NotFound => CartError::NotFound(path.to_path_buf()),
+            _ => CartError::Io(e),
+        })?;
+
+        let mut cart = Self::from_bytes(&rom)?;
+        cart.filename = path.to_string_lossy().into_owned();
+        cart.load_ram()?;
+        Ok(cart)
+    }
+
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Cart, CartError> {
+        let mut rom = Vec::new();
+        reader.read_to_end(&mut rom)?;
+        Self::from_bytes(&rom)
+    }
+
+    /// Build a cartridge from a ROM image in memory, picking the mapper from its header.
+    /// Nothing is saved to disk unless `filename` is set.
+    pub fn from_bytes(rom: &[u8]) -> Result<Cart, CartError> {
+        let header = RomHeader::parse(rom)?;
+
+        if let Some(expected) = header.rom_size_bytes() {
+            if rom.len() != expected {
+                return Err(CartError::SizeMismatch {
+                    expected,
+                    actual: rom.len(),
+                });
+            }
         }
+
+        let mapper = mapper::from_header(&header, rom.to_vec())?;
+
+        Ok(Cart {
+            filename: String::new(),
+            rom_head: header,
+            mapper,
+            saved_ram: Vec::new(),
+        })
+    }
+
+    pub fn read_rom(&self, address: u16) -> u8 {
+        self.mapper.read_rom(address)
+    }
+
+    pub fn write_rom(&mut self, address: u16, value: u8) {
+        self.mapper.write_register(address, value);
+    }
+
+    pub fn read_ram(&self, address: u16) -> u8 {
+        self.mapper.read_ram(address)
+    }
+
+    pub fn write_ram(&mut self, address: u16, value: u8) {
+        self.mapper.write_ram(address, value);
+    }
+
+    pub fn take_rumble_event(&mut self) -> Option<bool> {
+        self.mapper.take_rumble_event()
     }
 
     pub fn rom_size_bytes(code: u8) -> &'static str {
@@ -65,6 +123,17 @@ impl Cart {
             0x0B => "MMM01",
             0x0C => "MMM01 + RAM",
             0x0D => "MMM01 + RAM + BATTERY",
+            0x0F => "MBC3 + TIMER + BATTERY",
+            0x10 => "MBC3 + TIMER + RAM + BATTERY",
+            0x11 => "MBC3",
+            0x12 => "MBC3 + RAM",
+            0x13 => "MBC3 + RAM + BATTERY",
+            0x19 => "MBC5",
+            0x1A => "MBC5 + RAM",
+            0x1B => "MBC5 + RAM + BATTERY",
+            0x1C => "MBC5 + RUMBLE",
+            0x1D => "MBC5 + RUMBLE + RAM",
+            0x1E => "MBC5 + RUMBLE + RAM + BATTERY",
             _ => "UNKOWN",
         }
     }
@@ -217,57 +286,98 @@ impl Cart {
         }
     }
 
-    pub fn cart_load(&mut self) -> Result<()> {
-        // requesting memory for rom size.
-        self.rom_data = fs::read(&self.filename).unwrap();
-        self.rom_head = Some(Self::parse_header(&self.rom_data)?);
+    fn load_ram(&mut self) -> Result<(), CartError> {
+        let blank = match self.save_data() {
+            Some(data) => data,
+            None => return Ok(()),
+        };
+        let ram_size = self.mapper.battery_ram().map_or(0, |ram| ram.len());
+
+        let data = match fs::read(self.save_path()) {
+            Ok(data) => data,
+            // Nothing worth saving until the game writes to RAM
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                self.saved_ram = blank;
+                return Ok(());
+            }
+            Err(e) => return Err(CartError::Io(e)),
+        };
+
+        let footer_size = data.len().wrapping_sub(ram_size);
+        let has_rtc = self.mapper.rtc_footer().is_some();
+
+        // Refuse rather than overwrite a save that belongs to something else on exit
+        let valid = match footer_size {
+            0 => true,
+            FOOTER_SIZE | SHORT_FOOTER_SIZE => has_rtc,
+            _ => false,
+        };
+        if !valid {
+            return Err(CartError::InvalidSave {
+                path: self.save_path(),
+                expected: blank.len(),
+                actual: data.len(),
+            });
+        }
 
+        self.mapper.load_battery_ram(&data[..ram_size]);
+        if footer_size != 0 {
+            self.mapper.load_rtc_footer(&data[ram_size..]);
+        }
+        self.saved_ram = data;
         Ok(())
     }
 
-    fn parse_header(rom: &[u8]) -> Result<RomHeader> {
-        if rom.len() < 0x150 {
-            return Err(Error::new(ErrorKind::UnexpectedEof, "ROM too small"));
+    // Battery RAM followed by the RTC footer, if the cartridge has either
+    fn save_data(&self) -> Option<Vec<u8>> {
+        let mut data = self.mapper.battery_ram()?.to_vec();
+        if let Some(footer) = self.mapper.rtc_footer() {
+            data.extend_from_slice(&footer);
         }
+        Some(data)
+    }
 
-        //let entry = &rom[0x100..0x104];
-        //let logo  = &rom[0x104..0x134];
-        let title = &rom[0x134..0x144];
-        //let new_lic_code = u16::from_be_bytes([rom[0x144], rom[0x145]]);
-        //let sgb_flag = rom[0x146];
-        let type_val = rom[0x147];
-        let rom_size = rom[0x148];
-        let ram_size = rom[0x149];
-        let dest_code = rom[0x14A];
-        let lic_code = rom[0x14B];
-        let version = rom[0x14C];
-        let checksum = rom[0x14D];
-        let global_checksum = u16::from_be_bytes([rom[0x14E], rom[0x14F]]);
-
-        // Convert binary data into hex string representations
-        //let entry_str = entry.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ");
-        //let logo_str = logo.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ");
-
-        // Title as UTF-8 string
-        let title_str = std::str::from_utf8(title)
-            .unwrap_or("")
-            .trim_end_matches('\0')
-            .to_string();
-
-        Ok(RomHeader {
-            //entry: entry_str,
-            //logo: logo_str,
-            title: title_str,
-            //new_lic_code,
-            //sgb_flag,
-            type_val,
-            rom_size,
-            ram_size,
-            dest_code,
-            lic_code,
-            version,
-            checksum,
-            global_checksum,
-        })
+    /// Write battery-backed RAM and clock state next to the ROM if it changed since the last save.
+    /// Goes through a temporary file and a rename so a crash can't leave a torn save.
+    pub fn save_ram(&mut self) -> io::Result<()> {
+        let data = match self.save_data() {
+            Some(data)
+                if !self.filename.is_empty() && !data.is_empty() && !self.save_unchanged(&data) =>
+            {
+                data
+            }
+            _ => return Ok(()),
+        };
+
+        let path = self.save_path();
+        let temp_path = path.with_extension("sav.tmp");
+
+        let mut file = File::create(&temp_path)?;
+        file.write_all(&data)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, &path)?;
+
+        self.saved_ram = data;
+        Ok(())
+    }
+
+    // The RTC footer moves with the wall clock, so it is compared by clock state
+    fn save_unchanged(&self, data: &[u8]) -> bool {
+        if data.len() != self.saved_ram.len() {
+            return false;
+        }
+
+        let ram_size = self.mapper.battery_ram().map_or(0, |ram| ram.len());
+        let (ram, footer) = data.split_at(ram_size);
+        let (saved_ram, saved_footer) = self.saved_ram.split_at(ram_size);
+
+        ram == saved_ram && (footer.is_empty() || same_clock(footer, saved_footer))
+    }
+
+    fn save_path(&self) -> PathBuf {
+        PathBuf::from(&self.filename).with_extension("sav")
     }
 }
+
+#[cfg(test)]
+mod tests;
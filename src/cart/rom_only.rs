@@ -0,0 +1,46 @@
+use crate::cart::mapper::{ram_offset, Mapper};
+
+/// 32 KiB of ROM with no banking, optionally with up to 8 KiB of RAM.
+pub struct RomOnly {
+    rom: Vec<u8>,
+    ram: Vec<u8>,
+    has_battery: bool,
+}
+
+impl RomOnly {
+    pub fn new(rom: Vec<u8>, ram: Vec<u8>, has_battery: bool) -> Self {
+        Self {
+            rom,
+            ram,
+            has_battery,
+        }
+    }
+}
+
+impl Mapper for RomOnly {
+    fn read_rom(&self, address: u16) -> u8 {
+        self.rom.get(address as usize).copied().unwrap_or(0xFF)
+    }
+
+    fn write_register(&mut self, _address: u16, _value: u8) {}
+
+    fn read_ram(&self, address: u16) -> u8 {
+        match ram_offset(&self.ram, 0, address) {
+            Some(offset) => self.ram[offset],
+            None => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, address: u16, value: u8) {
+        if let Some(offset) = ram_offset(&self.ram, 0, address) {
+            self.ram[offset] = value;
+        }
+    }
+    fn battery_ram(&self) -> Option<&[u8]> {
+        self.has_battery.then_some(&self.ram[..])
+    }
+
+    fn load_battery_ram(&mut self, data: &[u8]) {
+        self.ram.copy_from_slice(data);
+    }
+}
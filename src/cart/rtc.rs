@@ -0,0 +1,195 @@
+//0x08  RTC S   Seconds 0-59
+//0x09  RTC M   Minutes 0-59
+//0x0A  RTC H   Hours 0-23
+//0x0B  RTC DL  Lower 8 bits of the day counter
+//0x0C  RTC DH  Bit 0 day counter bit 8, bit 6 halt, bit 7 day counter carry
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Size of the BGB / VBA-M clock footer appended to `.sav` files.
+pub const FOOTER_SIZE: usize = 48;
+
+// Older saves store the timestamp in 32 bits
+pub const SHORT_FOOTER_SIZE: usize = 44;
+
+/// Where the real-time clock gets the current time from, in seconds.
+pub trait TimeSource {
+    fn now(&self) -> u64;
+}
+
+/// Wall-clock time.
+pub struct SystemClock;
+
+impl TimeSource for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+/// Whether two footers hold the same clock state, ignoring how far it ran between them.
+pub fn same_clock(a: &[u8], b: &[u8]) -> bool {
+    let word = |footer: &[u8], i: usize| footer[i * 4];
+
+    // Seconds on the clock minus the time it was saved at, which stays put while it runs
+    let offset = |footer: &[u8]| {
+        let days = word(footer, 3) as i64 | ((word(footer, 4) & 0x01) as i64) << 8;
+        let seconds = days * 86400
+            + word(footer, 2) as i64 * 3600
+            + word(footer, 1) as i64 * 60
+            + word(footer, 0) as i64;
+        seconds - u64::from_le_bytes(footer[40..48].try_into().unwrap()) as i64
+    };
+
+    let halted = word(a, 4) & 0x40 != 0;
+    let clock_matches = if halted {
+        a[..20] == b[..20]
+    } else {
+        offset(a) == offset(b)
+    };
+
+    // Latched registers and the halt and carry flags have to match exactly
+    clock_matches && a[20..40] == b[20..40] && word(a, 4) & 0xC0 == word(b, 4) & 0xC0
+}
+
+#[derive(Clone)]
+pub struct Rtc {
+    seconds: u8,
+    minutes: u8,
+    hours: u8,
+    days: u16,
+    halted: bool,
+    carry: bool,
+
+    // Snapshot the game reads, taken by the latch sequence
+    latched: [u8; 5],
+
+    // Time the counters were last brought up to date
+    last_update: u64,
+}
+
+impl Rtc {
+    pub fn new(now: u64) -> Self {
+        Self {
+            seconds: 0,
+            minutes: 0,
+            hours: 0,
+            days: 0,
+            halted: false,
+            carry: false,
+            latched: [0; 5],
+            last_update: now,
+        }
+    }
+
+    pub fn latch(&mut self, now: u64) {
+        self.update(now);
+        self.latched = self.registers();
+    }
+
+    /// Read latched register 0x08-0x0C.
+    pub fn read(&self, register: u8) -> u8 {
+        match register {
+            0x08..=0x0C => self.latched[(register - 0x08) as usize],
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write(&mut self, register: u8, value: u8, now: u64) {
+        self.update(now);
+
+        match register {
+            0x08 => self.seconds = value & 0x3F,
+            0x09 => self.minutes = value & 0x3F,
+            0x0A => self.hours = value & 0x1F,
+            0x0B => self.days = (self.days & 0x100) | value as u16,
+            0x0C => {
+                self.days = (self.days & 0xFF) | ((value & 0x01) as u16) << 8;
+                self.halted = value & 0x40 != 0;
+                self.carry = value & 0x80 != 0;
+            }
+            _ => (),
+        }
+
+        // Writes also show up in the latched copy
+        if (0x08..=0x0C).contains(&register) {
+            self.latched[(register - 0x08) as usize] = self.registers()[(register - 0x08) as usize];
+        }
+    }
+
+    /// Serialize as the footer other emulators use: current then latched registers
+    /// as little-endian u32s, followed by the UNIX time they were saved at.
+    pub fn to_footer(&self, now: u64) -> [u8; FOOTER_SIZE] {
+        let mut rtc = self.clone();
+        rtc.update(now);
+
+        let mut footer = [0; FOOTER_SIZE];
+        let registers = rtc.registers().into_iter().chain(rtc.latched);
+        for (i, register) in registers.enumerate() {
+            footer[i * 4..i * 4 + 4].copy_from_slice(&(register as u32).to_le_bytes());
+        }
+        footer[40..48].copy_from_slice(&now.to_le_bytes());
+        footer
+    }
+
+    /// Restore from a footer. The time since it was saved is caught up on the next access.
+    pub fn load_footer(&mut self, footer: &[u8]) {
+        let word = |i: usize| footer[i * 4];
+
+        self.seconds = word(0) & 0x3F;
+        self.minutes = word(1) & 0x3F;
+        self.hours = word(2) & 0x1F;
+        self.days = word(3) as u16 | ((word(4) & 0x01) as u16) << 8;
+        self.halted = word(4) & 0x40 != 0;
+        self.carry = word(4) & 0x80 != 0;
+
+        for (i, latched) in self.latched.iter_mut().enumerate() {
+            *latched = word(5 + i);
+        }
+
+        let mut timestamp = [0; 8];
+        let length = (footer.len() - 40).min(8);
+        timestamp[..length].copy_from_slice(&footer[40..40 + length]);
+        self.last_update = u64::from_le_bytes(timestamp);
+    }
+
+    fn registers(&self) -> [u8; 5] {
+        [
+            self.seconds,
+            self.minutes,
+            self.hours,
+            self.days as u8,
+            (self.days >> 8) as u8 | (self.halted as u8) << 6 | (self.carry as u8) << 7,
+        ]
+    }
+
+    fn update(&mut self, now: u64) {
+        if !self.halted {
+            self.advance(now.saturating_sub(self.last_update));
+        }
+        self.last_update = now;
+    }
+
+    fn advance(&mut self, seconds: u64) {
+        if seconds == 0 {
+            return;
+        }
+
+        let seconds = self.seconds as u64 + seconds;
+        let minutes = self.minutes as u64 + seconds / 60;
+        let hours = self.hours as u64 + minutes / 60;
+        let days = self.days as u64 + hours / 24;
+
+        self.seconds = (seconds % 60) as u8;
+        self.minutes = (minutes % 60) as u8;
+        self.hours = (hours % 24) as u8;
+        self.days = (days % 512) as u16;
+
+        // The carry stays set until the game clears it
+        if days >= 512 {
+            self.carry = true;
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cart::error::CartError;
+use crate::cart::Cart;
+
+// MBC1 + RAM + BATTERY ROM with 8 KiB of RAM, written to its own temp directory
+fn write_rom(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ferrisboy-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut rom = vec![0; 0x8000];
+    rom[0x147] = 0x03;
+    rom[0x149] = 0x02;
+
+    let path = dir.join("game.gb");
+    fs::write(&path, rom).unwrap();
+    path
+}
+
+fn load(path: &Path) -> Result<Cart, CartError> {
+    Cart::from_path(path)
+}
+
+#[test]
+fn ram_survives_save_and_load() {
+    let rom_path = write_rom("round-trip");
+
+    let mut cart = load(&rom_path).unwrap();
+    cart.write_rom(0x0000, 0x0A);
+    cart.write_ram(0xA123, 0x42);
+    cart.save_ram().unwrap();
+
+    let save_path = rom_path.with_extension("sav");
+    assert_eq!(fs::read(&save_path).unwrap().len(), 0x2000);
+    assert!(!rom_path.with_extension("sav.tmp").exists());
+
+    let mut cart = load(&rom_path).unwrap();
+    cart.write_rom(0x0000, 0x0A);
+    assert_eq!(cart.read_ram(0xA123), 0x42);
+
+    fs::remove_dir_all(rom_path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn unchanged_ram_is_not_written() {
+    let rom_path = write_rom("unchanged");
+
+    let mut cart = load(&rom_path).unwrap();
+    cart.save_ram().unwrap();
+
+    assert!(!rom_path.with_extension("sav").exists());
+
+    fs::remove_dir_all(rom_path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn wrong_size_save_is_rejected() {
+    let rom_path = write_rom("wrong-size");
+    fs::write(rom_path.with_extension("sav"), [0; 0x100]).unwrap();
+
+    assert!(matches!(
+        load(&rom_path),
+        Err(CartError::InvalidSave { actual: 0x100, .. })
+    ));
+
+    fs::remove_dir_all(rom_path.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,67 @@
+use crate::cart::header::{
+    global_checksum, header_checksum, CartType, CgbSupport, MapperKind, RomHeader, SgbSupport,
+};
+
+fn test_rom() -> Vec<u8> {
+    let mut rom = vec![0; 0x8000];
+    rom[0x134..0x13A].copy_from_slice(b"FERRIS");
+    rom[0x143] = 0x80;
+    rom[0x144..0x146].copy_from_slice(b"01");
+    rom[0x146] = 0x03;
+    rom[0x147] = 0x13;
+    rom[0x149] = 0x03;
+    rom[0x14B] = 0x33;
+
+    rom[0x14D] = header_checksum(&rom);
+    let global = global_checksum(&rom);
+    rom[0x14E..0x150].copy_from_slice(&global.to_be_bytes());
+    rom
+}
+
+#[test]
+fn decodes_typed_fields() {
+    let header = RomHeader::parse(&test_rom()).unwrap();
+
+    assert_eq!(header.title, "FERRIS");
+    assert_eq!(header.cgb_support, CgbSupport::Enhanced);
+    assert_eq!(header.sgb_support, SgbSupport::Supported);
+    assert_eq!(header.ram_size_bytes, 0x8000);
+    assert_eq!(header.licensee(), "Nintendo R&D1");
+    assert_eq!(
+        header.cart_type,
+        CartType {
+            mapper: MapperKind::Mbc3,
+            ram: true,
+            battery: true,
+            timer: false,
+            rumble: false,
+        }
+    );
+}
+
+#[test]
+fn valid_checksums_have_no_checksum_warnings() {
+    let header = RomHeader::parse(&test_rom()).unwrap();
+
+    assert_eq!(header.computed_checksum, header.checksum);
+    assert_eq!(header.computed_global_checksum, header.global_checksum);
+    assert!(!header.warnings().iter().any(|w| w.contains("checksum")));
+}
+
+#[test]
+fn checksum_mismatches_are_warnings() {
+    let mut rom = test_rom();
+    rom[0x14D] ^= 0xFF;
+    rom[0x4000] = 0x01;
+
+    let header = RomHeader::parse(&rom).unwrap();
+    let warnings = header.warnings();
+
+    assert!(warnings.iter().any(|w| w.starts_with("header checksum")));
+    assert!(warnings.iter().any(|w| w.starts_with("global checksum")));
+}
+
+#[test]
+fn rom_too_small() {
+    assert!(RomHeader::parse(&[0; 0x14F]).is_err());
+}
@@ -0,0 +1,67 @@
+use std::io::Cursor;
+
+use crate::cart::error::CartError;
+use crate::cart::Cart;
+
+fn rom_with_type(cart_type: u8) -> Vec<u8> {
+    let mut rom = vec![0; 0x8000];
+    rom[0x147] = cart_type;
+    rom
+}
+
+#[test]
+fn missing_file_is_not_found() {
+    let result = Cart::from_path("/nonexistent/ferrisboy/missing.gb");
+
+    assert!(matches!(result, Err(CartError::NotFound(_))));
+}
+
+#[test]
+fn truncated_header_is_too_small() {
+    let result = Cart::from_bytes(&[0; 0x100]);
+
+    assert!(matches!(result, Err(CartError::TooSmall(0x100))));
+}
+
+#[test]
+fn unknown_mapper_is_unsupported() {
+    let result = Cart::from_bytes(&rom_with_type(0xFE));
+
+    assert!(matches!(result, Err(CartError::UnsupportedMapper(0xFE))));
+}
+
+#[test]
+fn rom_shorter_than_header_size_is_rejected() {
+    let mut rom = rom_with_type(0x01);
+    rom[0x148] = 0x02;
+
+    let result = Cart::from_bytes(&rom);
+
+    assert!(matches!(
+        result,
+        Err(CartError::SizeMismatch {
+            expected: 0x20000,
+            actual: 0x8000
+        })
+    ));
+}
+
+#[test]
+fn loads_from_reader() {
+    let mut rom = rom_with_type(0x00);
+    rom[0x134..0x138].copy_from_slice(b"READ");
+
+    let cart = Cart::from_reader(Cursor::new(rom)).unwrap();
+
+    assert_eq!(cart.rom_head.title, "READ");
+}
+
+#[test]
+fn bad_checksum_is_reported() {
+    let cart = Cart::from_bytes(&rom_with_type(0x00)).unwrap();
+
+    assert!(matches!(
+        cart.rom_head.verify_checksum(),
+        Err(CartError::BadChecksum { expected: 0x00, .. })
+    ));
+}
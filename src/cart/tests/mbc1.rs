@@ -0,0 +1,84 @@
+use crate::cart::Cart;
+
+// ROM where the first byte of every bank holds the bank number
+fn banked_rom(cart_type: u8, banks: usize, ram_size: u8) -> Vec<u8> {
+    let mut rom = vec![0; banks * 0x4000];
+    for bank in 0..banks {
+        rom[bank * 0x4000] = bank as u8;
+    }
+    rom[0x147] = cart_type;
+    rom[0x148] = (banks / 2).trailing_zeros() as u8;
+    rom[0x149] = ram_size;
+    rom
+}
+
+#[test]
+fn rom_only_ignores_writes() {
+    let mut cart = Cart::from_bytes(&banked_rom(0x00, 2, 0x00)).unwrap();
+
+    cart.write_rom(0x4000, 0x12);
+
+    assert_eq!(cart.read_rom(0x4000), 0x01);
+    assert_eq!(cart.read_ram(0xA000), 0xFF);
+}
+
+#[test]
+fn bank_zero_selects_bank_one() {
+    let mut cart = Cart::from_bytes(&banked_rom(0x01, 8, 0x00)).unwrap();
+
+    assert_eq!(cart.read_rom(0x4000), 1);
+
+    cart.write_rom(0x2000, 0x05);
+    assert_eq!(cart.read_rom(0x4000), 5);
+
+    cart.write_rom(0x2000, 0x00);
+    assert_eq!(cart.read_rom(0x4000), 1);
+}
+
+#[test]
+fn large_rom_uses_upper_bank_bits() {
+    let mut cart = Cart::from_bytes(&banked_rom(0x01, 128, 0x00)).unwrap();
+
+    cart.write_rom(0x2000, 0x03);
+    cart.write_rom(0x4000, 0x02);
+    assert_eq!(cart.read_rom(0x4000), 0x43);
+
+    // Mode 1 also switches the low bank
+    assert_eq!(cart.read_rom(0x0000), 0x00);
+    cart.write_rom(0x6000, 0x01);
+    assert_eq!(cart.read_rom(0x0000), 0x40);
+}
+
+#[test]
+fn ram_needs_enabling() {
+    let mut cart = Cart::from_bytes(&banked_rom(0x03, 4, 0x03)).unwrap();
+
+    cart.write_ram(0xA000, 0x42);
+    assert_eq!(cart.read_ram(0xA000), 0xFF);
+
+    cart.write_rom(0x0000, 0x0A);
+    cart.write_ram(0xA000, 0x42);
+    assert_eq!(cart.read_ram(0xA000), 0x42);
+
+    cart.write_rom(0x0000, 0x00);
+    assert_eq!(cart.read_ram(0xA000), 0xFF);
+}
+
+#[test]
+fn ram_banking_mode_switches_ram_bank() {
+    let mut cart = Cart::from_bytes(&banked_rom(0x03, 4, 0x03)).unwrap();
+    cart.write_rom(0x0000, 0x0A);
+    cart.write_rom(0x6000, 0x01);
+
+    cart.write_rom(0x4000, 0x01);
+    cart.write_ram(0xA000, 0x11);
+    cart.write_rom(0x4000, 0x02);
+    cart.write_ram(0xA000, 0x22);
+
+    cart.write_rom(0x4000, 0x01);
+    assert_eq!(cart.read_ram(0xA000), 0x11);
+
+    // Mode 0 always uses RAM bank 0
+    cart.write_rom(0x6000, 0x00);
+    assert_eq!(cart.read_ram(0xA000), 0x00);
+}
@@ -0,0 +1,63 @@
+use crate::cart::mapper::Mapper;
+use crate::cart::mbc2::Mbc2;
+
+fn setup_mbc2(has_battery: bool) -> Mbc2 {
+    let mut rom = vec![0; 16 * 0x4000];
+    for bank in 0..16 {
+        rom[bank * 0x4000] = bank as u8;
+    }
+    Mbc2::new(rom, has_battery)
+}
+
+#[test]
+fn address_bit_8_selects_register() {
+    let mut mbc2 = setup_mbc2(false);
+
+    // Bit 8 clear, RAM enable rather than bank select
+    mbc2.write_register(0x2000, 0x0A);
+    assert_eq!(mbc2.read_rom(0x4000), 1);
+
+    mbc2.write_register(0x2100, 0x0F);
+    assert_eq!(mbc2.read_rom(0x4000), 15);
+
+    mbc2.write_register(0x0100, 0x00);
+    assert_eq!(mbc2.read_rom(0x4000), 1);
+}
+
+#[test]
+fn ram_stores_low_nibble_and_echoes() {
+    let mut mbc2 = setup_mbc2(false);
+    mbc2.write_register(0x0000, 0x0A);
+
+    mbc2.write_ram(0xA010, 0x5C);
+
+    assert_eq!(mbc2.read_ram(0xA010), 0xFC);
+    assert_eq!(mbc2.read_ram(0xA210), 0xFC);
+    assert_eq!(mbc2.read_ram(0xBE10), 0xFC);
+}
+
+#[test]
+fn ram_disabled_reads_open_bus() {
+    let mut mbc2 = setup_mbc2(false);
+
+    mbc2.write_ram(0xA000, 0x05);
+
+    assert_eq!(mbc2.read_ram(0xA000), 0xFF);
+}
+
+#[test]
+fn battery_ram_round_trips() {
+    let mut mbc2 = setup_mbc2(true);
+    mbc2.write_register(0x0000, 0x0A);
+    mbc2.write_ram(0xA001, 0x07);
+
+    let saved = mbc2.battery_ram().unwrap().to_vec();
+    assert_eq!(saved.len(), 512);
+
+    let mut restored = setup_mbc2(true);
+    restored.load_battery_ram(&saved);
+    restored.write_register(0x0000, 0x0A);
+    assert_eq!(restored.read_ram(0xA001), 0xF7);
+
+    assert!(setup_mbc2(false).battery_ram().is_none());
+}
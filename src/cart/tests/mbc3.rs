@@ -0,0 +1,164 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use crate::cart::mapper::Mapper;
+use crate::cart::mbc3::Mbc3;
+use crate::cart::rtc::{same_clock, TimeSource};
+
+struct TestClock(Rc<Cell<u64>>);
+
+impl TimeSource for TestClock {
+    fn now(&self) -> u64 {
+        self.0.get()
+    }
+}
+
+fn setup_mbc3() -> (Mbc3, Rc<Cell<u64>>) {
+    let mut rom = vec![0; 128 * 0x4000];
+    for bank in 0..128 {
+        rom[bank * 0x4000] = bank as u8;
+    }
+
+    let time = Rc::new(Cell::new(1000));
+    let mut mbc3 = Mbc3::new(
+        rom,
+        vec![0; 0x8000],
+        true,
+        true,
+        Box::new(TestClock(Rc::clone(&time))),
+    );
+    mbc3.write_register(0x0000, 0x0A);
+    (mbc3, time)
+}
+
+fn latch(mbc3: &mut Mbc3) {
+    mbc3.write_register(0x6000, 0x00);
+    mbc3.write_register(0x6000, 0x01);
+}
+
+fn read_rtc(mbc3: &mut Mbc3, register: u8) -> u8 {
+    mbc3.write_register(0x4000, register);
+    mbc3.read_ram(0xA000)
+}
+
+#[test]
+fn switches_all_128_rom_banks() {
+    let (mut mbc3, _) = setup_mbc3();
+
+    mbc3.write_register(0x2000, 0x7F);
+    assert_eq!(mbc3.read_rom(0x4000), 0x7F);
+
+    mbc3.write_register(0x2000, 0x00);
+    assert_eq!(mbc3.read_rom(0x4000), 0x01);
+}
+
+#[test]
+fn switches_ram_banks() {
+    let (mut mbc3, _) = setup_mbc3();
+
+    for bank in 0..4 {
+        mbc3.write_register(0x4000, bank);
+        mbc3.write_ram(0xA123, bank + 0x10);
+    }
+
+    mbc3.write_register(0x4000, 0x02);
+    assert_eq!(mbc3.read_ram(0xA123), 0x12);
+}
+
+#[test]
+fn rtc_reads_latched_time() {
+    let (mut mbc3, time) = setup_mbc3();
+
+    // 1 day, 2 hours, 3 minutes, 4 seconds
+    time.set(1000 + 86400 + 2 * 3600 + 3 * 60 + 4);
+    assert_eq!(read_rtc(&mut mbc3, 0x08), 0x00);
+
+    latch(&mut mbc3);
+    time.set(time.get() + 10);
+
+    assert_eq!(read_rtc(&mut mbc3, 0x08), 4);
+    assert_eq!(read_rtc(&mut mbc3, 0x09), 3);
+    assert_eq!(read_rtc(&mut mbc3, 0x0A), 2);
+    assert_eq!(read_rtc(&mut mbc3, 0x0B), 1);
+    assert_eq!(read_rtc(&mut mbc3, 0x0C), 0);
+}
+
+#[test]
+fn latch_needs_zero_then_one() {
+    let (mut mbc3, time) = setup_mbc3();
+
+    time.set(1005);
+    mbc3.write_register(0x6000, 0x01);
+
+    assert_eq!(read_rtc(&mut mbc3, 0x08), 0);
+}
+
+#[test]
+fn halt_stops_the_clock() {
+    let (mut mbc3, time) = setup_mbc3();
+
+    mbc3.write_register(0x4000, 0x0C);
+    mbc3.write_ram(0xA000, 0x40);
+    time.set(2000);
+    latch(&mut mbc3);
+
+    assert_eq!(read_rtc(&mut mbc3, 0x08), 0);
+    assert_eq!(read_rtc(&mut mbc3, 0x0C), 0x40);
+}
+
+#[test]
+fn day_counter_overflow_sets_carry() {
+    let (mut mbc3, time) = setup_mbc3();
+
+    mbc3.write_register(0x4000, 0x0B);
+    mbc3.write_ram(0xA000, 0xFF);
+    mbc3.write_register(0x4000, 0x0C);
+    mbc3.write_ram(0xA000, 0x01);
+
+    time.set(1000 + 86400);
+    latch(&mut mbc3);
+
+    assert_eq!(read_rtc(&mut mbc3, 0x0B), 0x00);
+    assert_eq!(read_rtc(&mut mbc3, 0x0C), 0x80);
+}
+
+#[test]
+fn rtc_footer_fast_forwards_on_load() {
+    let (mut mbc3, time) = setup_mbc3();
+
+    // 5 minutes in, latched at 3 minutes
+    time.set(1000 + 180);
+    latch(&mut mbc3);
+    time.set(1000 + 300);
+
+    let footer = mbc3.rtc_footer().unwrap();
+    assert_eq!(footer.len(), 48);
+    assert_eq!(footer[4], 5);
+    assert_eq!(footer[24], 3);
+    assert_eq!(u64::from_le_bytes(footer[40..48].try_into().unwrap()), 1300);
+
+    // Reload an hour later
+    let (mut restored, time) = setup_mbc3();
+    time.set(1300 + 3600);
+    restored.load_rtc_footer(&footer);
+
+    assert_eq!(read_rtc(&mut restored, 0x09), 3);
+    latch(&mut restored);
+    assert_eq!(read_rtc(&mut restored, 0x09), 5);
+    assert_eq!(read_rtc(&mut restored, 0x0A), 1);
+}
+
+#[test]
+fn running_clock_footers_compare_equal() {
+    let (mut mbc3, time) = setup_mbc3();
+    time.set(1000 + 90);
+    let before = mbc3.rtc_footer().unwrap();
+
+    time.set(1000 + 90 + 86400 + 5);
+    assert!(same_clock(&mbc3.rtc_footer().unwrap(), &before));
+
+    // Setting the clock is a change worth saving
+    mbc3.write_register(0x4000, 0x09);
+    mbc3.write_ram(0xA000, 0x20);
+    assert!(!same_clock(&mbc3.rtc_footer().unwrap(), &before));
+}
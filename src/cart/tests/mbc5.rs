@@ -0,0 +1,67 @@
+use crate::cart::Cart;
+
+fn banked_rom(cart_type: u8, banks: usize) -> Vec<u8> {
+    let mut rom = vec![0; banks * 0x4000];
+    for bank in 0..banks {
+        rom[bank * 0x4000] = bank as u8;
+        rom[bank * 0x4000 + 1] = (bank >> 8) as u8;
+    }
+    rom[0x147] = cart_type;
+    rom[0x148] = (banks / 2).trailing_zeros() as u8;
+    rom[0x149] = 0x04;
+    rom
+}
+
+#[test]
+fn nine_bit_rom_bank() {
+    let mut cart = Cart::from_bytes(&banked_rom(0x19, 512)).unwrap();
+
+    cart.write_rom(0x2000, 0x23);
+    cart.write_rom(0x3000, 0x01);
+    assert_eq!(cart.read_rom(0x4000), 0x23);
+    assert_eq!(cart.read_rom(0x4001), 0x01);
+
+    // Unlike MBC1 bank 0 can be mapped to 0x4000
+    cart.write_rom(0x2000, 0x00);
+    cart.write_rom(0x3000, 0x00);
+    assert_eq!(cart.read_rom(0x4000), 0x00);
+}
+
+#[test]
+fn sixteen_ram_banks() {
+    let mut cart = Cart::from_bytes(&banked_rom(0x1A, 2)).unwrap();
+    cart.write_rom(0x0000, 0x0A);
+
+    for bank in 0..16 {
+        cart.write_rom(0x4000, bank);
+        cart.write_ram(0xA000, bank);
+    }
+
+    cart.write_rom(0x4000, 0x0F);
+    assert_eq!(cart.read_ram(0xA000), 0x0F);
+    cart.write_rom(0x4000, 0x03);
+    assert_eq!(cart.read_ram(0xA000), 0x03);
+}
+
+#[test]
+fn rumble_bit_raises_events() {
+    let mut cart = Cart::from_bytes(&banked_rom(0x1C, 2)).unwrap();
+
+    assert_eq!(cart.take_rumble_event(), None);
+
+    cart.write_rom(0x4000, 0x08);
+    assert_eq!(cart.take_rumble_event(), Some(true));
+    assert_eq!(cart.take_rumble_event(), None);
+
+    cart.write_rom(0x4000, 0x00);
+    assert_eq!(cart.take_rumble_event(), Some(false));
+}
+
+#[test]
+fn no_rumble_without_motor() {
+    let mut cart = Cart::from_bytes(&banked_rom(0x19, 2)).unwrap();
+
+    cart.write_rom(0x4000, 0x08);
+
+    assert_eq!(cart.take_rumble_event(), None);
+}
@@ -0,0 +1,7 @@
+mod battery;
+mod header;
+mod loading;
+mod mbc1;
+mod mbc2;
+mod mbc3;
+mod mbc5;
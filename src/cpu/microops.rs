@@ -48,6 +48,10 @@ pub enum MicroOp {
         dst: Reg16,
         src: Reg16,
     },
+    LdReg16FromReg16 {
+        dst: Reg16,
+        src: Reg16,
+    },
     LdMemImm16FromReg16 {
         src: Reg16,
     },
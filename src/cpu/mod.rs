@@ -8,6 +8,12 @@ use crate::cpu::logging::opcode_info;
 use crate::cpu::microops::MicroOp;
 use crate::cpu::registers::{Flags, Reg16, Reg8, Registers};
 use crate::interconnect::Interconnect;
+use crate::joypad::Button;
+use crate::model::Model;
+use crate::serial::device::SerialDevice;
+
+// 70224 dots / 4
+const CYCLES_PER_FRAME: u64 = 17556;
 
 pub struct Cpu {
     regs: Registers,
@@ -20,16 +26,28 @@ pub struct Cpu {
 
     interrupt_enable_next: bool,
 
-    //halted: bool,
+    halted: bool,
+
+    halt_bug: bool,
+
+    stopped: bool,
+
     inter: Interconnect,
 
     cycles: u64,
+
+    // M-cycles of the current step already clocked by bus accesses
+    ticked: u32,
+
+    pub trace: bool,
 }
 
 impl Cpu {
+    /// Starts in the boot ROM if one is mapped, otherwise in the state the
+    /// boot ROM of the interconnect's model leaves behind.
     pub fn new(inter: Interconnect) -> Cpu {
         let regs = Registers {
-            pc: 0x100,
+            pc: 0x0000,
             sp: 0,
             a: 0,
             b: 0,
@@ -43,7 +61,7 @@ impl Cpu {
             // ir: 0,
         };
 
-        Cpu {
+        let mut cpu = Cpu {
             regs,
             flags: Flags {
                 z: false,
@@ -54,14 +72,94 @@ impl Cpu {
 
             alu: Alu::new(),
             inter,
-            interrupt: true,
-            interrupt_enable_next: true,
-            //halted: false,
+            interrupt: false,
+            interrupt_enable_next: false,
+            halted: false,
+            halt_bug: false,
+            stopped: false,
             cycles: 0,
+            ticked: 0,
+            trace: false,
+        };
+
+        if !cpu.inter.boot_rom_mapped() {
+            cpu.skip_boot_rom();
         }
+        cpu
+    }
+
+    fn skip_boot_rom(&mut self) {
+        let header = &self.inter.cart.rom_head;
+        let cgb_mode = self.inter.cgb_mode();
+
+        // The DMG boot ROM leaves H and C set unless the header checksum is 0
+        let checksum_flags = if header.checksum == 0 { 0x80 } else { 0xB0 };
+
+        let [af, bc, de, hl] = match self.inter.model {
+            Model::Dmg0 => [0x0100, 0xFF13, 0x00C1, 0x8403],
+            Model::Dmg => [0x0100 | checksum_flags, 0x0013, 0x00D8, 0x014D],
+            Model::Mgb => [0xFF00 | checksum_flags, 0x0013, 0x00D8, 0x014D],
+            Model::Sgb => [0x0100, 0x0014, 0x0000, 0xC060],
+            Model::Sgb2 => [0xFF00, 0x0014, 0x0000, 0xC060],
+            Model::Cgb if cgb_mode => [0x1180, 0x0000, 0xFF56, 0x000D],
+            Model::Cgb => [0x1180, 0x0000, 0x0008, 0x007C],
+            Model::Agb if cgb_mode => [0x1100, 0x0100, 0xFF56, 0x000D],
+            Model::Agb => [0x1100, 0x0100, 0x0008, 0x007C],
+        };
+
+        self.regs.set16(Reg16::AF, af);
+        self.regs.set16(Reg16::BC, bc);
+        self.regs.set16(Reg16::DE, de);
+        self.regs.set16(Reg16::HL, hl);
+        self.flags = Flags::from_u8(af as u8);
+        self.regs.sp = 0xFFFE;
+        self.regs.pc = 0x0100;
+
+        self.inter.skip_boot_rom();
     }
 
     pub fn step(&mut self) {
+        if self.stopped {
+            // The system clock is halted, only a selected joypad line going low wakes it up
+            if self.inter.joypad.any_line_low() {
+                self.stopped = false;
+            }
+            self.cycles += 1;
+            return;
+        }
+
+        let cycles_before = self.cycles;
+        self.ticked = 0;
+
+        if self.halted && self.inter.interrupts.pending() != 0 {
+            self.halted = false;
+        }
+
+        if self.halted {
+            // Peripherals keep running while the CPU sleeps
+            self.cycles += 1;
+        } else if !self.service_interrupt() {
+            self.execute_instruction();
+        }
+
+        // Bus accesses have clocked the peripherals up to their own M-cycle, this is the rest
+        let elapsed = (self.cycles - cycles_before) as u32;
+        self.inter.tick(elapsed - self.ticked);
+
+        // VRAM DMA halts the CPU while everything else keeps running
+        loop {
+            let stall = self.inter.take_dma_stall();
+            if stall == 0 {
+                break;
+            }
+            self.cycles += stall as u64;
+            self.inter.tick(stall);
+        }
+    }
+
+    fn execute_instruction(&mut self) {
+        // EI takes effect after the instruction following it
+        let enable_interrupts = self.interrupt_enable_next;
         let pc_before_op = self.regs.pc;
 
         let opcode = self.fetch8();
@@ -69,23 +167,27 @@ impl Cpu {
         let (micro_ops, cycles) = if opcode == 0xCB {
             let cb_opcode = self.fetch8();
 
-            println!("PC: {:#06X} | Opcode: CB {:#04X}", pc_before_op, cb_opcode);
+            if self.trace {
+                println!("PC: {:#06X} | Opcode: CB {:#04X}", pc_before_op, cb_opcode);
+            }
 
             self.cb_decode(cb_opcode)
         } else {
-            let (mnemonic, bytes, log_cycles) = opcode_info(opcode);
+            if self.trace {
+                let (mnemonic, bytes, log_cycles) = opcode_info(opcode);
 
-            let mut instr_bytes = vec![opcode];
-            if bytes > 1 {
-                for i in 1..bytes {
-                    instr_bytes.push(self.inter.read_byte(pc_before_op + i as u16));
+                let mut instr_bytes = vec![opcode];
+                if bytes > 1 {
+                    for i in 1..bytes {
+                        instr_bytes.push(self.inter.read_byte(pc_before_op + i as u16));
+                    }
                 }
-            }
 
-            println!(
-                "PC: {:#06X} | Opcode: {:#04X} | Mnemonic: {:<10} | Bytes: {:?} | Cycles: {}",
-                pc_before_op, opcode, mnemonic, instr_bytes, log_cycles
-            );
+                println!(
+                    "PC: {:#06X} | Opcode: {:#04X} | Mnemonic: {:<10} | Bytes: {:?} | Cycles: {}",
+                    pc_before_op, opcode, mnemonic, instr_bytes, log_cycles
+                );
+            }
 
             self.decode(opcode)
         };
@@ -95,22 +197,163 @@ impl Cpu {
         }
 
         self.cycles += cycles as u64;
+
+        if enable_interrupts && self.interrupt_enable_next {
+            self.interrupt = true;
+            self.interrupt_enable_next = false;
+        }
     }
 
+    /// Dispatch the highest priority pending interrupt when IME is set.
+    /// Takes 5 machine cycles: 2 wait states, 2 for pushing PC and 1 for the jump.
+    fn service_interrupt(&mut self) -> bool {
+        if !self.interrupt || self.inter.interrupts.highest_pending().is_none() {
+            return false;
+        }
+
+        self.interrupt = false;
+        self.interrupt_enable_next = false;
+
+        // EI; HALT with an interrupt already pending: it returns to the HALT, which runs again
+        let pc = if std::mem::take(&mut self.halt_bug) {
+            self.regs.pc.wrapping_sub(1)
+        } else {
+            self.regs.pc
+        };
+        self.push((pc >> 8) as u8);
+
+        // The high byte push can land on IE (SP = 0x0000) and change what is pending
+        let interrupt = self.inter.interrupts.highest_pending();
+        self.push((pc & 0xFF) as u8);
+
+        self.regs.pc = match interrupt {
+            Some(interrupt) => {
+                self.inter.interrupts.acknowledge(interrupt);
+                interrupt.vector()
+            }
+            None => 0x0000,
+        };
+
+        self.cycles += 5;
+        true
+    }
+
+    // A frame is twice as many CPU cycles in double speed
+    fn frame_cycles(&self) -> u64 {
+        CYCLES_PER_FRAME << self.inter.double_speed() as u64
+    }
+
+    /// Run until the PPU finishes a frame, or one frame's worth of cycles if the LCD is off.
+    pub fn run_frame(&mut self) {
+        let frame_end = self.cycles + self.frame_cycles();
+
+        while self.cycles < frame_end {
+            self.step();
+            if self.inter.ppu.take_frame_ready() {
+                break;
+            }
+        }
+    }
+
+    /// Like `run_frame`, keeping a linked Game Boy in step cycle by cycle.
+    pub fn run_linked_frame(&mut self, peer: &mut Cpu) {
+        let frame_end = self.cycles + self.frame_cycles();
+
+        while self.cycles < frame_end {
+            self.step();
+            while peer.cycles < self.cycles {
+                peer.step();
+            }
+            // The peer has no screen, don't let its frames pile up
+            peer.inter.ppu.take_frame_ready();
+
+            if self.inter.ppu.take_frame_ready() {
+                break;
+            }
+        }
+    }
+
+    /// Persist battery-backed cartridge RAM.
+    pub fn save_ram(&mut self) -> std::io::Result<()> {
+        self.inter.cart.save_ram()
+    }
+
+    /// New rumble motor state if the cartridge switched it since the last call.
+    pub fn take_rumble_event(&mut self) -> Option<bool> {
+        self.inter.cart.take_rumble_event()
+    }
+
+    pub fn connect_serial(&mut self, device: Box<dyn SerialDevice>) {
+        self.inter.serial.connect(device);
+    }
+
+    pub fn framebuffer(&self) -> &[u32] {
+        self.inter.ppu.framebuffer()
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.inter.apu.set_sample_rate(sample_rate);
+    }
+
+    /// Interleaved stereo samples generated since the last call.
+    pub fn drain_audio(&mut self) -> Vec<f32> {
+        self.inter.apu.drain_samples()
+    }
+
+    /// Replace the held buttons with `buttons`, a mask of `Button::bit` values.
+    pub fn set_buttons(&mut self, buttons: u8) {
+        self.inter
+            .joypad
+            .set_buttons(buttons, &mut self.inter.interrupts);
+    }
+
+    pub fn press(&mut self, button: Button) {
+        self.inter.joypad.press(button, &mut self.inter.interrupts);
+    }
+
+    pub fn release(&mut self, button: Button) {
+        self.inter
+            .joypad
+            .release(button, &mut self.inter.interrupts);
+    }
+
+    // Each bus access takes an M-cycle, the peripherals are clocked through it first
+    // so the access sees them as they are at that point of the instruction
+    fn read_byte(&mut self, address: u16) -> u8 {
+        self.tick_access();
+        self.inter.read_byte(address)
+    }
+
+    fn write_byte(&mut self, address: u16, value: u8) {
+        self.tick_access();
+        self.inter.write_byte(address, value);
+    }
+
+    fn tick_access(&mut self) {
+        self.inter.tick(1);
+        self.ticked += 1;
+    }
+
+    // Not counted here, the cycles in the decode tables already include operand fetches
     fn fetch8(&mut self) -> u8 {
-        let byte = self.inter.read_byte(self.regs.get16(Reg16::PC));
-        self.regs
-            .set16(Reg16::PC, self.regs.get16(Reg16::PC).wrapping_add(1));
-        self.cycles += 1; // 1 machine cycle for fetch
+        let byte = self.read_byte(self.regs.get16(Reg16::PC));
+
+        if self.halt_bug {
+            // PC fails to increment, so this byte is read again
+            self.halt_bug = false;
+        } else {
+            self.regs
+                .set16(Reg16::PC, self.regs.get16(Reg16::PC).wrapping_add(1));
+        }
         byte
     }
 
     fn fetch16(&mut self) -> u16 {
-        let lo = self.inter.read_byte(self.regs.get16(Reg16::PC)) as u16;
+        let lo = self.read_byte(self.regs.get16(Reg16::PC)) as u16;
         self.regs
             .set16(Reg16::PC, self.regs.get16(Reg16::PC).wrapping_add(1));
 
-        let hi = self.inter.read_byte(self.regs.get16(Reg16::PC)) as u16;
+        let hi = self.read_byte(self.regs.get16(Reg16::PC)) as u16;
         self.regs
             .set16(Reg16::PC, self.regs.get16(Reg16::PC).wrapping_add(1));
 
@@ -119,11 +362,11 @@ impl Cpu {
 
     fn push(&mut self, value: u8) {
         self.regs.sp = self.regs.sp.wrapping_sub(1);
-        self.inter.write_byte(self.regs.sp, value);
+        self.write_byte(self.regs.sp, value);
     }
 
     fn pop(&mut self) -> u8 {
-        let value = self.inter.read_byte(self.regs.sp);
+        let value = self.read_byte(self.regs.sp);
         self.regs.sp = self.regs.sp.wrapping_add(1);
 
         value
@@ -251,7 +494,7 @@ impl Cpu {
                 }],
                 2,
             ),
-            0x46 => (vec![MicroOp::BitRegHl { bit: (0) }], 4),
+            0x46 => (vec![MicroOp::BitRegHl { bit: (0) }], 3),
             0x47 => (
                 vec![MicroOp::BitReg8 {
                     bit: (0),
@@ -301,7 +544,7 @@ impl Cpu {
                 }],
                 2,
             ),
-            0x4E => (vec![MicroOp::BitRegHl { bit: (1) }], 4),
+            0x4E => (vec![MicroOp::BitRegHl { bit: (1) }], 3),
             0x4F => (
                 vec![MicroOp::BitReg8 {
                     bit: (1),
@@ -351,7 +594,7 @@ impl Cpu {
                 }],
                 2,
             ),
-            0x56 => (vec![MicroOp::BitRegHl { bit: (2) }], 4),
+            0x56 => (vec![MicroOp::BitRegHl { bit: (2) }], 3),
             0x57 => (
                 vec![MicroOp::BitReg8 {
                     bit: (2),
@@ -401,7 +644,7 @@ impl Cpu {
                 }],
                 2,
             ),
-            0x5E => (vec![MicroOp::BitRegHl { bit: (3) }], 4),
+            0x5E => (vec![MicroOp::BitRegHl { bit: (3) }], 3),
             0x5F => (
                 vec![MicroOp::BitReg8 {
                     bit: (3),
@@ -451,7 +694,7 @@ impl Cpu {
                 }],
                 2,
             ),
-            0x66 => (vec![MicroOp::BitRegHl { bit: (4) }], 4),
+            0x66 => (vec![MicroOp::BitRegHl { bit: (4) }], 3),
             0x67 => (
                 vec![MicroOp::BitReg8 {
                     bit: (4),
@@ -501,7 +744,7 @@ impl Cpu {
                 }],
                 2,
             ),
-            0x6E => (vec![MicroOp::BitRegHl { bit: (5) }], 4),
+            0x6E => (vec![MicroOp::BitRegHl { bit: (5) }], 3),
             0x6F => (
                 vec![MicroOp::BitReg8 {
                     bit: (5),
@@ -551,7 +794,7 @@ impl Cpu {
                 }],
                 2,
             ),
-            0x76 => (vec![MicroOp::BitRegHl { bit: (6) }], 4),
+            0x76 => (vec![MicroOp::BitRegHl { bit: (6) }], 3),
             0x77 => (
                 vec![MicroOp::BitReg8 {
                     bit: (6),
@@ -601,7 +844,7 @@ impl Cpu {
                 }],
                 2,
             ),
-            0x7E => (vec![MicroOp::BitRegHl { bit: (7) }], 4),
+            0x7E => (vec![MicroOp::BitRegHl { bit: (7) }], 3),
             0x7F => (
                 vec![MicroOp::BitReg8 {
                     bit: (7),
@@ -1301,7 +1544,7 @@ impl Cpu {
                 }],
                 2,
             ),
-            0xEE => (vec![MicroOp::ResRegHl { bit: (5) }], 2),
+            0xEE => (vec![MicroOp::ResRegHl { bit: (5) }], 4),
             0xEF => (
                 vec![MicroOp::ResReg8 {
                     bit: (5),
@@ -2596,7 +2839,7 @@ impl Cpu {
                 }],
                 2,
             ),
-            0xD1 => (vec![MicroOp::PopReg16 { reg: (Reg16::DE) }], 1),
+            0xD1 => (vec![MicroOp::PopReg16 { reg: (Reg16::DE) }], 3),
             0xD2 => (
                 {
                     let addr = self.fetch16();
@@ -2744,7 +2987,7 @@ impl Cpu {
             0xF7 => (vec![MicroOp::Restart { vector: (0x0030) }], 4),
             0xF8 => (vec![MicroOp::LdHLSPPlusR8], 3),
             0xF9 => (
-                vec![MicroOp::LdReg16FromMem {
+                vec![MicroOp::LdReg16FromReg16 {
                     dst: (Reg16::SP),
                     src: (Reg16::HL),
                 }],
@@ -2771,9 +3014,25 @@ impl Cpu {
         match op {
             MicroOp::Nop => {}
 
-            MicroOp::Halt => {}
+            MicroOp::Halt => {
+                if !self.interrupt && self.inter.interrupts.pending() != 0 {
+                    // HALT bug: HALT is skipped and the next opcode byte is read twice
+                    self.halt_bug = true;
+                } else {
+                    self.halted = true;
+                }
+            }
+
+            MicroOp::Stop => {
+                // STOP is followed by a padding byte, skipped without a bus access
+                self.regs.pc = self.regs.pc.wrapping_add(1);
+                self.inter.reset_div();
 
-            MicroOp::Stop => {}
+                // A CGB speed switch pauses inside switch_speed, then the CPU carries on by itself
+                if !self.inter.switch_speed() {
+                    self.stopped = true;
+                }
+            }
 
             //Load instructions
             MicroOp::LdReg8FromReg8 { dst, src } => {
@@ -2783,7 +3042,7 @@ impl Cpu {
 
             MicroOp::LdReg8FromMem { dst, src } => {
                 let addr = self.regs.get16(src);
-                let value = self.inter.read_byte(addr);
+                let value = self.read_byte(addr);
                 self.regs.set8(dst, value);
             }
             MicroOp::LdReg8FromImm { dst } => {
@@ -2794,56 +3053,60 @@ impl Cpu {
             MicroOp::LdMemFromReg8 { addr, src } => {
                 let value = self.regs.get8(src);
                 let address = self.regs.get16(addr);
-                self.inter.write_byte(address, value);
-                self.cycles += 1;
+                self.write_byte(address, value);
             }
 
             MicroOp::LdA8FromA { offset } => {
                 let addr = 0xFF00u16 + offset as u16;
                 let value = self.regs.get8(Reg8::A);
-                self.inter.write_byte(addr, value);
+                self.write_byte(addr, value);
             }
 
             MicroOp::LdAFromA8 { offset } => {
                 let addr = 0xFF00u16 + offset as u16;
-                let value = self.inter.read_byte(addr);
+                let value = self.read_byte(addr);
                 self.regs.set8(Reg8::A, value);
             }
 
             MicroOp::LdCFromA => {
                 let addr: u16 = 0xFF00u16 + self.regs.get8(Reg8::C) as u16;
                 let value = self.regs.get8(Reg8::A);
-                self.inter.write_byte(addr, value);
+                self.write_byte(addr, value);
             }
 
             MicroOp::LdAFromC => {
                 let addr: u16 = 0xFF00u16 + self.regs.get8(Reg8::C) as u16;
-                let value = self.inter.read_byte(addr);
+                let value = self.read_byte(addr);
                 self.regs.set8(Reg8::A, value);
             }
 
             MicroOp::LdMemFromA { addr } => {
                 let value = self.regs.get8(Reg8::A);
-                self.inter.write_byte(addr, value);
+                self.write_byte(addr, value);
             }
 
             MicroOp::LdReg8FromMemImm16 { dst } => {
                 let addr = self.fetch16();
-                let val = self.inter.read_byte(addr);
+                let val = self.read_byte(addr);
                 self.regs.set8(dst, val);
             }
 
             MicroOp::LdReg16FromMem { dst, src } => {
                 let addr = self.regs.get16(src);
-                let lo = self.inter.read_byte(addr) as u16;
-                let hi = self.inter.read_byte(addr.wrapping_add(1)) as u16;
+                let lo = self.read_byte(addr) as u16;
+                let hi = self.read_byte(addr.wrapping_add(1)) as u16;
                 let value = (hi << 8) | lo;
                 self.regs.set16(dst, value);
             }
 
+            MicroOp::LdReg16FromReg16 { dst, src } => {
+                let value = self.regs.get16(src);
+                self.regs.set16(dst, value);
+            }
+
             MicroOp::LdReg8FromMemIncHL { dst } => {
                 let hl = self.regs.get16(Reg16::HL);
-                let value = self.inter.read_byte(hl);
+                let value = self.read_byte(hl);
 
                 self.regs.set8(dst, value);
 
@@ -2855,7 +3118,7 @@ impl Cpu {
 
                 let value = self.regs.get8(src);
 
-                self.inter.write_byte(hl, value);
+                self.write_byte(hl, value);
 
                 self.regs.set16(Reg16::HL, hl.wrapping_add(1));
             }
@@ -2865,14 +3128,14 @@ impl Cpu {
 
                 let value = self.regs.get8(src);
 
-                self.inter.write_byte(hl, value);
+                self.write_byte(hl, value);
 
                 self.regs.set16(Reg16::HL, hl.wrapping_sub(1));
             }
 
             MicroOp::LdReg8FromMemDecHL { dst } => {
                 let hl = self.regs.get16(Reg16::HL);
-                let value = self.inter.read_byte(hl);
+                let value = self.read_byte(hl);
 
                 self.regs.set8(dst, value);
 
@@ -2889,13 +3152,13 @@ impl Cpu {
                 let lo_val = (value & 0x00FF) as u8;
                 let hi_val = (value >> 8) as u8;
 
-                self.inter.write_byte(addr, lo_val);
-                self.inter.write_byte(addr + 1, hi_val);
+                self.write_byte(addr, lo_val);
+                self.write_byte(addr + 1, hi_val);
             }
 
             MicroOp::LdReg8FromReg16 { dst, src } => {
                 let addr = self.regs.get16(src);
-                let value = self.inter.read_byte(addr);
+                let value = self.read_byte(addr);
                 self.regs.set8(dst, value);
             }
 
@@ -2903,7 +3166,7 @@ impl Cpu {
                 let hl = self.regs.get16(addr);
 
                 let value = self.fetch8();
-                self.inter.write_byte(hl, value);
+                self.write_byte(hl, value);
             }
 
             //Logical
@@ -2945,7 +3208,7 @@ impl Cpu {
             MicroOp::AddReg8Mem { dst, src } => {
                 let a = self.regs.get8(dst);
                 let addr = self.regs.get16(src);
-                let value = self.inter.read_byte(addr);
+                let value = self.read_byte(addr);
 
                 let alu_out = self.alu.add_8bit(a, value);
 
@@ -3006,7 +3269,7 @@ impl Cpu {
 
             MicroOp::AddCarry8Mem { dst, src } => {
                 let mem = self.regs.get16(src);
-                let value = self.inter.read_byte(mem);
+                let value = self.read_byte(mem);
                 let a = self.regs.get8(dst);
                 let carry = self.flags.get_flag('c') as u8;
 
@@ -3053,7 +3316,7 @@ impl Cpu {
 
             /*  MicroOp::SubReg8Mem { dst, src } => {
                 let mem = self.regs.get16(src);
-                let value = self.inter.read_byte(mem);
+                let value = self.read_byte(mem);
                 let a = self.regs.get8(dst);
 
                 let alu_out = self.alu.sub_8bit(a, value);
@@ -3093,7 +3356,7 @@ impl Cpu {
 
             MicroOp::SubCarry8Mem { dst, src } => {
                 let mem = self.regs.get16(src);
-                let value = self.inter.read_byte(mem);
+                let value = self.read_byte(mem);
                 let a = self.regs.get8(dst);
                 let carry = self.flags.get_flag('c');
 
@@ -3140,7 +3403,7 @@ impl Cpu {
 
             MicroOp::XorReg8Mem { dst, src } => {
                 let mem = self.regs.get16(src);
-                let value = self.inter.read_byte(mem);
+                let value = self.read_byte(mem);
                 let a = self.regs.get8(dst);
 
                 let alu_out = self.alu.xor_8bit(a, value);
@@ -3183,7 +3446,7 @@ impl Cpu {
             MicroOp::CpReg8Mem { dst, src } => {
                 let a = self.regs.get8(dst);
                 let b = self.regs.get16(src);
-                let value = self.inter.read_byte(b);
+                let value = self.read_byte(b);
 
                 let alu_out = self.alu.cp_8bit(a, value);
 
@@ -3222,7 +3485,7 @@ impl Cpu {
             MicroOp::OrReg8Mem { dst, src } => {
                 let a = self.regs.get8(dst);
                 let b = self.regs.get16(src);
-                let value = self.inter.read_byte(b);
+                let value = self.read_byte(b);
 
                 let alu_out = self.alu.or_8bit(a, value);
 
@@ -3266,7 +3529,7 @@ impl Cpu {
 
             MicroOp::AndReg8Mem { dst, src } => {
                 let mem = self.regs.get16(src);
-                let value = self.inter.read_byte(mem);
+                let value = self.read_byte(mem);
 
                 let a = self.regs.get8(dst);
                 let alu_out = self.alu.and_8bit(a, value);
@@ -3298,15 +3561,15 @@ impl Cpu {
                 let sp = self.regs.get16(Reg16::SP);
 
                 self.regs.set16(Reg16::SP, sp - 2);
-                self.inter.write_byte(sp - 2, (value >> 8) as u8);
-                self.inter.write_byte(sp - 1, (value & 0xFF) as u8);
+                self.write_byte(sp - 2, (value >> 8) as u8);
+                self.write_byte(sp - 1, (value & 0xFF) as u8);
             }
 
             MicroOp::PopReg16 { reg } => {
-                let lo = self.inter.read_byte(self.regs.sp);
+                let lo = self.read_byte(self.regs.sp);
                 self.regs.sp = self.regs.sp.wrapping_add(1);
 
-                let hi = self.inter.read_byte(self.regs.sp);
+                let hi = self.read_byte(self.regs.sp);
                 self.regs.sp = self.regs.sp.wrapping_add(1);
 
                 let val = ((hi as u16) << 8) | lo as u16;
@@ -3458,7 +3721,6 @@ impl Cpu {
 
             MicroOp::Ei => {
                 self.interrupt_enable_next = true;
-                self.interrupt = true;
             }
 
             MicroOp::Cpl => {
@@ -3634,7 +3896,7 @@ impl Cpu {
 
             MicroOp::RlRegHl => {
                 let addr = self.regs.get16(Reg16::HL);
-                let val = self.inter.read_byte(addr);
+                let val = self.read_byte(addr);
                 let c_flag = self.flags.get_flag('c');
 
                 let alu_out = self.alu.rl_byte(val, c_flag);
@@ -3644,11 +3906,11 @@ impl Cpu {
                 self.flags.set_flag('n', alu_out.n);
                 self.flags.set_flag('h', alu_out.h);
                 self.flags.set_flag('c', alu_out.c);
-                self.inter.write_byte(addr, result);
+                self.write_byte(addr, result);
             }
             MicroOp::RlcRegHl => {
                 let addr = self.regs.get16(Reg16::HL);
-                let val = self.inter.read_byte(addr);
+                let val = self.read_byte(addr);
 
                 let alu_out = self.alu.rlc_byte(val);
                 let result = alu_out.result;
@@ -3657,11 +3919,11 @@ impl Cpu {
                 self.flags.set_flag('n', alu_out.n);
                 self.flags.set_flag('h', alu_out.h);
                 self.flags.set_flag('c', alu_out.c);
-                self.inter.write_byte(addr, result);
+                self.write_byte(addr, result);
             }
             MicroOp::RrRegHl => {
                 let addr = self.regs.get16(Reg16::HL);
-                let val = self.inter.read_byte(addr);
+                let val = self.read_byte(addr);
                 let c_flag = self.flags.get_flag('c');
 
                 let alu_out = self.alu.rr_byte(val, c_flag);
@@ -3671,11 +3933,11 @@ impl Cpu {
                 self.flags.set_flag('n', alu_out.n);
                 self.flags.set_flag('h', alu_out.h);
                 self.flags.set_flag('c', alu_out.c);
-                self.inter.write_byte(addr, result);
+                self.write_byte(addr, result);
             }
             MicroOp::RrcRegHl => {
                 let addr = self.regs.get16(Reg16::HL);
-                let val = self.inter.read_byte(addr);
+                let val = self.read_byte(addr);
 
                 let alu_out = self.alu.rrc_byte(val);
                 let result = alu_out.result;
@@ -3684,11 +3946,11 @@ impl Cpu {
                 self.flags.set_flag('n', alu_out.n);
                 self.flags.set_flag('h', alu_out.h);
                 self.flags.set_flag('c', alu_out.c);
-                self.inter.write_byte(addr, result);
+                self.write_byte(addr, result);
             }
             MicroOp::SlaRegHl => {
                 let addr = self.regs.get16(Reg16::HL);
-                let val = self.inter.read_byte(addr);
+                let val = self.read_byte(addr);
 
                 let alu_out = self.alu.sla_byte(val);
                 let result = alu_out.result;
@@ -3697,11 +3959,11 @@ impl Cpu {
                 self.flags.set_flag('n', alu_out.n);
                 self.flags.set_flag('h', alu_out.h);
                 self.flags.set_flag('c', alu_out.c);
-                self.inter.write_byte(addr, result);
+                self.write_byte(addr, result);
             }
             MicroOp::SraRegHl => {
                 let addr = self.regs.get16(Reg16::HL);
-                let val = self.inter.read_byte(addr);
+                let val = self.read_byte(addr);
 
                 let alu_out = self.alu.sra_byte(val);
                 let result = alu_out.result;
@@ -3710,11 +3972,11 @@ impl Cpu {
                 self.flags.set_flag('n', alu_out.n);
                 self.flags.set_flag('h', alu_out.h);
                 self.flags.set_flag('c', alu_out.c);
-                self.inter.write_byte(addr, result);
+                self.write_byte(addr, result);
             }
             MicroOp::SrlRegHl => {
                 let addr = self.regs.get16(Reg16::HL);
-                let val = self.inter.read_byte(addr);
+                let val = self.read_byte(addr);
 
                 let alu_out = self.alu.srl_byte(val);
                 let result = alu_out.result;
@@ -3723,11 +3985,11 @@ impl Cpu {
                 self.flags.set_flag('n', alu_out.n);
                 self.flags.set_flag('h', alu_out.h);
                 self.flags.set_flag('c', alu_out.c);
-                self.inter.write_byte(addr, result);
+                self.write_byte(addr, result);
             }
             MicroOp::SwapRegHl => {
                 let addr = self.regs.get16(Reg16::HL);
-                let val = self.inter.read_byte(addr);
+                let val = self.read_byte(addr);
 
                 let alu_out = self.alu.swap_byte(val);
                 let result = alu_out.result;
@@ -3736,7 +3998,7 @@ impl Cpu {
                 self.flags.set_flag('n', alu_out.n);
                 self.flags.set_flag('h', alu_out.h);
                 self.flags.set_flag('c', alu_out.c);
-                self.inter.write_byte(addr, result);
+                self.write_byte(addr, result);
             }
             MicroOp::AddImmToSP { imm } => {
                 let sp = self.regs.sp;
@@ -3755,7 +4017,7 @@ impl Cpu {
 
             MicroOp::BitRegHl { bit } => {
                 let addr = self.regs.get16(Reg16::HL);
-                let val = self.inter.read_byte(addr);
+                let val = self.read_byte(addr);
 
                 let alu_out = self.alu.bit_byte(val, bit);
 
@@ -3776,11 +4038,11 @@ impl Cpu {
 
             MicroOp::ResRegHl { bit } => {
                 let addr = self.regs.get16(Reg16::HL);
-                let val = self.inter.read_byte(addr);
+                let val = self.read_byte(addr);
 
                 let result = self.alu.res_byte(val, bit);
 
-                self.inter.write_byte(addr, result);
+                self.write_byte(addr, result);
             }
 
             MicroOp::ResReg8 { bit, reg } => {
@@ -3792,11 +4054,11 @@ impl Cpu {
 
             MicroOp::SetRegHl { bit } => {
                 let addr = self.regs.get16(Reg16::HL);
-                let val = self.inter.read_byte(addr);
+                let val = self.read_byte(addr);
 
                 let result = self.alu.set_byte(val, bit);
 
-                self.inter.write_byte(addr, result);
+                self.write_byte(addr, result);
             }
 
             MicroOp::SetReg8 { bit, reg } => {
@@ -0,0 +1,37 @@
+use crate::cart::Cart;
+use crate::cpu::{Cpu, Reg16};
+use crate::interconnect::Interconnect;
+use crate::model::Model;
+
+fn setup_interconnect() -> Interconnect {
+    Interconnect::new(Cart::from_bytes(&[0; 0x8000]).unwrap(), Model::Dmg)
+}
+
+#[test]
+fn starts_at_entry_point_without_boot_rom() {
+    let cpu = Cpu::new(setup_interconnect());
+
+    assert_eq!(cpu.regs.pc, 0x0100);
+}
+
+#[test]
+fn starts_in_boot_rom() {
+    let mut inter = setup_interconnect();
+    inter.load_boot_rom(vec![0; 0x100]).unwrap();
+
+    let cpu = Cpu::new(inter);
+
+    assert_eq!(cpu.regs.pc, 0x0000);
+}
+
+#[test]
+fn skipping_boot_rom_sets_post_boot_state() {
+    let mut cpu = Cpu::new(setup_interconnect());
+
+    // Header checksum of a blank ROM is 0, so the boot ROM leaves H and C clear
+    assert_eq!(cpu.regs.get16(Reg16::AF), 0x0180);
+    assert_eq!(cpu.regs.get16(Reg16::SP), 0xFFFE);
+    assert_eq!(cpu.inter.read_byte(0xFF40), 0x91);
+    assert_eq!(cpu.inter.read_byte(0xFF26), 0xF1);
+    assert_eq!(cpu.inter.read_byte(0xFF04), 0xAB);
+}
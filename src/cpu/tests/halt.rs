@@ -0,0 +1,158 @@
+use crate::cart::Cart;
+use crate::cpu::{Cpu, Reg16, Reg8};
+use crate::interconnect::Interconnect;
+use crate::interrupts::Interrupt;
+use crate::joypad::Button;
+use crate::model::Model;
+
+fn setup_cpu(program: &[u8]) -> Cpu {
+    let mut rom = vec![0; 0x8000];
+    rom[..program.len()].copy_from_slice(program);
+
+    let mut cpu = Cpu::new(Interconnect::new(
+        Cart::from_bytes(&rom).unwrap(),
+        Model::Dmg,
+    ));
+    cpu.regs.pc = 0x0000;
+    cpu.regs.sp = 0xFFFE;
+
+    // Start from a blank machine rather than the post-boot state
+    cpu.regs.set16(Reg16::AF, 0x0000);
+    cpu.inter.write_byte(0xFF04, 0x00);
+    cpu.inter.write_byte(0xFF0F, 0x00);
+    cpu.inter.write_byte(0xFF40, 0x00);
+    cpu
+}
+
+#[test]
+fn halt_sleeps_until_interrupt_pending() {
+    // HALT, NOP
+    let mut cpu = setup_cpu(&[0x76, 0x00]);
+    cpu.inter.write_byte(0xFFFF, Interrupt::Timer.bit());
+
+    cpu.step();
+    for _ in 0..10 {
+        cpu.step();
+    }
+
+    assert!(cpu.halted);
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0001);
+
+    cpu.inter.interrupts.request(Interrupt::Timer);
+    cpu.step();
+
+    assert!(!cpu.halted);
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0002);
+}
+
+#[test]
+fn halt_keeps_clocking_peripherals() {
+    // HALT
+    let mut cpu = setup_cpu(&[0x76]);
+    cpu.inter.write_byte(0xFF40, 0x80);
+    cpu.inter.write_byte(0xFFFF, Interrupt::VBlank.bit());
+
+    while cpu.halted || cpu.regs.get16(Reg16::PC) == 0x0000 {
+        cpu.step();
+    }
+
+    assert_eq!(cpu.inter.read_byte(0xFF44), 144);
+}
+
+#[test]
+fn halt_wakes_into_interrupt_with_ime() {
+    // HALT
+    let mut cpu = setup_cpu(&[0x76]);
+    cpu.interrupt = true;
+    cpu.inter.write_byte(0xFFFF, Interrupt::Serial.bit());
+
+    cpu.step();
+    cpu.inter.interrupts.request(Interrupt::Serial);
+    cpu.step();
+
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0058);
+    assert_eq!(cpu.inter.read_byte(0xFFFC), 0x01);
+}
+
+#[test]
+fn halt_bug_repeats_next_byte() {
+    // HALT, INC A, NOP
+    let mut cpu = setup_cpu(&[0x76, 0x3C, 0x00]);
+    cpu.inter.write_byte(0xFFFF, Interrupt::VBlank.bit());
+    cpu.inter.interrupts.request(Interrupt::VBlank);
+
+    cpu.step();
+    assert!(!cpu.halted);
+
+    cpu.step();
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0001);
+
+    cpu.step();
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0002);
+    assert_eq!(cpu.regs.get8(Reg8::A), 2);
+}
+
+#[test]
+fn ei_halt_with_pending_interrupt_returns_to_halt() {
+    // EI, HALT, with INC B; RETI at the timer vector
+    let mut program = vec![0; 0x52];
+    program[..2].copy_from_slice(&[0xFB, 0x76]);
+    program[0x50..].copy_from_slice(&[0x04, 0xD9]);
+    let mut cpu = setup_cpu(&program);
+    cpu.inter.write_byte(0xFFFF, Interrupt::Timer.bit());
+    cpu.inter.interrupts.request(Interrupt::Timer);
+
+    cpu.step();
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0050);
+    assert_eq!(cpu.inter.read_byte(0xFFFC), 0x01);
+
+    cpu.step();
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0051);
+    assert_eq!(cpu.regs.get8(Reg8::B), 1);
+
+    cpu.step();
+    cpu.step();
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0002);
+    assert!(cpu.halted);
+    assert_eq!(cpu.regs.get8(Reg8::B), 1);
+}
+
+#[test]
+fn stop_waits_for_joypad_and_resets_div() {
+    // STOP, 0x00, NOP
+    let mut cpu = setup_cpu(&[0x10, 0x00, 0x00]);
+    cpu.inter.tick(300);
+    assert_eq!(cpu.inter.read_byte(0xFF04), 0x04);
+
+    cpu.step();
+    cpu.step();
+
+    assert!(cpu.stopped);
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0002);
+    assert_eq!(cpu.inter.read_byte(0xFF04), 0x00);
+
+    cpu.inter.write_byte(0xFF00, 0x20);
+    cpu.press(Button::Down);
+    cpu.step();
+    cpu.step();
+
+    assert!(!cpu.stopped);
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0003);
+}
+
+#[test]
+fn stop_ignores_pending_joypad_flag() {
+    // STOP, 0x00, NOP
+    let mut cpu = setup_cpu(&[0x10, 0x00, 0x00]);
+    cpu.inter.write_byte(0xFF00, 0x20);
+    cpu.step();
+
+    cpu.inter.interrupts.request(Interrupt::Joypad);
+    cpu.step();
+    cpu.step();
+
+    assert!(cpu.stopped);
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0002);
+}
@@ -0,0 +1,116 @@
+use crate::cart::Cart;
+use crate::cpu::{Cpu, Reg16};
+use crate::interconnect::Interconnect;
+use crate::interrupts::Interrupt;
+use crate::model::Model;
+
+fn setup_cpu(program: &[u8]) -> Cpu {
+    let mut rom = vec![0; 0x8000];
+    rom[..program.len()].copy_from_slice(program);
+
+    let mut cpu = Cpu::new(Interconnect::new(
+        Cart::from_bytes(&rom).unwrap(),
+        Model::Dmg,
+    ));
+    cpu.regs.pc = 0x0000;
+    cpu.regs.sp = 0xFFFE;
+
+    // Start from a blank machine rather than the post-boot state
+    cpu.regs.set16(Reg16::AF, 0x0000);
+    cpu.inter.write_byte(0xFF04, 0x00);
+    cpu.inter.write_byte(0xFF0F, 0x00);
+    cpu.inter.write_byte(0xFF40, 0x00);
+    cpu
+}
+
+#[test]
+fn dispatch_pushes_pc_and_jumps_to_vector() {
+    let mut cpu = setup_cpu(&[]);
+    cpu.regs.pc = 0x0012;
+    cpu.interrupt = true;
+    cpu.inter.write_byte(0xFFFF, Interrupt::Timer.bit());
+    cpu.inter.interrupts.request(Interrupt::Timer);
+
+    cpu.step();
+
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0050);
+    assert_eq!(cpu.regs.get16(Reg16::SP), 0xFFFC);
+    assert_eq!(cpu.inter.read_byte(0xFFFC), 0x12);
+    assert_eq!(cpu.inter.read_byte(0xFFFD), 0x00);
+    assert_eq!(cpu.inter.read_byte(0xFF0F) & 0x1F, 0);
+    assert!(!cpu.interrupt);
+    assert_eq!(cpu.cycles, 5);
+}
+
+#[test]
+fn dispatch_follows_priority() {
+    let mut cpu = setup_cpu(&[]);
+    cpu.interrupt = true;
+    cpu.inter.write_byte(0xFFFF, 0x1F);
+    cpu.inter
+        .write_byte(0xFF0F, Interrupt::Joypad.bit() | Interrupt::Stat.bit());
+
+    cpu.step();
+
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0048);
+    assert_eq!(cpu.inter.read_byte(0xFF0F), 0xE0 | Interrupt::Joypad.bit());
+}
+
+#[test]
+fn no_dispatch_without_ime() {
+    let mut cpu = setup_cpu(&[]);
+    cpu.inter.write_byte(0xFFFF, 0x1F);
+    cpu.inter.interrupts.request(Interrupt::VBlank);
+
+    cpu.step();
+
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0001);
+    assert_eq!(cpu.inter.read_byte(0xFF0F), 0xE1);
+}
+
+#[test]
+fn ei_takes_effect_after_next_instruction() {
+    // EI, NOP, NOP
+    let mut cpu = setup_cpu(&[0xFB, 0x00, 0x00]);
+    cpu.inter.write_byte(0xFFFF, Interrupt::VBlank.bit());
+    cpu.inter.interrupts.request(Interrupt::VBlank);
+
+    cpu.step();
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0001);
+
+    cpu.step();
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0002);
+
+    cpu.step();
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0040);
+}
+
+#[test]
+fn di_after_ei_cancels_enable() {
+    // EI, DI, NOP
+    let mut cpu = setup_cpu(&[0xFB, 0xF3, 0x00]);
+    cpu.inter.write_byte(0xFFFF, Interrupt::VBlank.bit());
+    cpu.inter.interrupts.request(Interrupt::VBlank);
+
+    cpu.step();
+    cpu.step();
+    cpu.step();
+
+    assert!(!cpu.interrupt);
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0003);
+}
+
+#[test]
+fn push_over_ie_cancels_dispatch() {
+    let mut cpu = setup_cpu(&[]);
+    cpu.regs.pc = 0x0012;
+    cpu.regs.sp = 0x0000;
+    cpu.interrupt = true;
+    cpu.inter.write_byte(0xFFFF, Interrupt::Timer.bit());
+    cpu.inter.interrupts.request(Interrupt::Timer);
+
+    cpu.step();
+
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0000);
+    assert_eq!(cpu.inter.read_byte(0xFF0F) & 0x1F, Interrupt::Timer.bit());
+}
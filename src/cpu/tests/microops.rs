@@ -1,10 +1,11 @@
+use crate::cart::Cart;
 use crate::cpu::microops::MicroOp;
 use crate::cpu::{Cpu, Reg16, Reg8};
 use crate::interconnect::Interconnect;
+use crate::model::Model;
 
 fn setup_cpu() -> Cpu {
-    let memory = vec![0; 0x100];
-    let inter = Interconnect::new(memory);
+    let inter = Interconnect::new(Cart::from_bytes(&[0; 0x8000]).unwrap(), Model::Dmg);
     Cpu::new(inter)
 }
 
@@ -42,8 +43,8 @@ fn ld_reg8_from_imm() {
     let mut cpu = setup_cpu();
     let imm = 0x42;
 
-    cpu.inter.write_byte(0x0000, imm);
-    cpu.regs.pc = 0x0000;
+    cpu.inter.write_byte(0xC100, imm);
+    cpu.regs.pc = 0xC100;
     cpu.regs.set8(Reg8::B, 0x00);
 
     cpu.execute_microop(MicroOp::LdReg8FromImm { dst: (Reg8::B) });
@@ -124,11 +125,11 @@ fn ld_c_from_a() {
     let mut cpu = setup_cpu();
 
     cpu.regs.set8(Reg8::A, 0x99);
-    cpu.regs.set8(Reg8::C, 0x10);
+    cpu.regs.set8(Reg8::C, 0x80);
 
     cpu.execute_microop(MicroOp::LdCFromA);
 
-    let addr = 0xFF00u16 + 0x10;
+    let addr = 0xFF00u16 + 0x80;
 
     assert_eq!(cpu.inter.read_byte(addr), 0x99);
     assert_eq!(cpu.regs.get8(Reg8::A), 0x99);
@@ -138,8 +139,8 @@ fn ld_c_from_a() {
 fn ld_a_from_c() {
     let mut cpu = setup_cpu();
 
-    cpu.regs.set8(Reg8::C, 0x10);
-    let addr = 0xFF00u16 + 0x10;
+    cpu.regs.set8(Reg8::C, 0x80);
+    let addr = 0xFF00u16 + 0x80;
     cpu.inter.write_byte(addr, 0x81);
     cpu.execute_microop(MicroOp::LdAFromC);
 
@@ -178,16 +179,16 @@ fn ld_mem_imm16_from_reg16() {
 
     cpu.regs.set16(Reg16::HL, 0xFEED);
 
-    cpu.regs.pc = 0x0000;
-    cpu.inter.write_byte(0x0000, 0x00);
-    cpu.inter.write_byte(0x0001, 0xC0);
+    cpu.regs.pc = 0xC100;
+    cpu.inter.write_byte(0xC100, 0x00);
+    cpu.inter.write_byte(0xC101, 0xC0);
 
     cpu.execute_microop(MicroOp::LdMemImm16FromReg16 { src: Reg16::HL });
 
     assert_eq!(cpu.inter.read_byte(0xC000), 0xED);
     assert_eq!(cpu.inter.read_byte(0xC001), 0xFE);
 
-    assert_eq!(cpu.regs.pc, 0x0002);
+    assert_eq!(cpu.regs.pc, 0xC102);
 }
 
 #[test]
@@ -210,15 +211,15 @@ fn ld_mem_from_imm8() {
     let mut cpu = setup_cpu();
 
     cpu.regs.set16(Reg16::HL, 0xC000);
-    cpu.regs.pc = 0x0000;
+    cpu.regs.pc = 0xC100;
 
-    cpu.inter.write_byte(0x0000, 0x99);
+    cpu.inter.write_byte(0xC100, 0x99);
 
     cpu.execute_microop(MicroOp::LdMemFromImm8 { addr: Reg16::HL });
 
     assert_eq!(cpu.inter.read_byte(0xC000), 0x99);
     assert_eq!(cpu.regs.get16(Reg16::HL), 0xC000);
-    assert_eq!(cpu.regs.pc, 0x0001);
+    assert_eq!(cpu.regs.pc, 0xC101);
 }
 
 #[test]
@@ -1390,13 +1391,14 @@ fn di_disables_interrupts() {
 }
 
 #[test]
-fn ei_enables_interrupts() {
+fn ei_schedules_interrupt_enable() {
     let mut cpu = setup_cpu();
     cpu.interrupt = false;
 
     cpu.execute_microop(MicroOp::Ei);
 
-    assert!(cpu.interrupt);
+    assert!(!cpu.interrupt);
+    assert!(cpu.interrupt_enable_next);
 }
 
 #[test]
@@ -1 +1,6 @@
+mod boot;
+mod halt;
+mod interrupts;
 mod microops;
+mod speed;
+mod timing;
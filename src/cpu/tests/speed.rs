@@ -0,0 +1,83 @@
+use crate::cart::Cart;
+use crate::cpu::{Cpu, Reg16};
+use crate::interconnect::Interconnect;
+use crate::model::Model;
+
+fn setup_cpu(program: &[u8]) -> Cpu {
+    let mut rom = vec![0; 0x8000];
+    rom[0x0143] = 0x80;
+    rom[..program.len()].copy_from_slice(program);
+
+    let mut cpu = Cpu::new(Interconnect::new(
+        Cart::from_bytes(&rom).unwrap(),
+        Model::Cgb,
+    ));
+    cpu.regs.pc = 0x0000;
+    cpu
+}
+
+#[test]
+fn stop_switches_speed_when_armed() {
+    // STOP, 0x00, NOP
+    let mut cpu = setup_cpu(&[0x10, 0x00, 0x00]);
+    assert_eq!(cpu.inter.read_byte(0xFF4D), 0x7E);
+
+    cpu.inter.write_byte(0xFF4D, 0x01);
+    assert_eq!(cpu.inter.read_byte(0xFF4D), 0x7F);
+
+    cpu.step();
+
+    assert!(!cpu.stopped);
+    assert_eq!(cpu.inter.read_byte(0xFF4D), 0xFE);
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0002);
+
+    cpu.step();
+    assert_eq!(cpu.regs.get16(Reg16::PC), 0x0003);
+}
+
+#[test]
+fn double_speed_halves_ppu_rate() {
+    let mut cpu = setup_cpu(&[0x10, 0x00]);
+    cpu.inter.write_byte(0xFF4D, 0x01);
+    cpu.step();
+
+    // Restart the LCD so LY counts from the top
+    cpu.inter.write_byte(0xFF40, 0x00);
+    cpu.inter.write_byte(0xFF40, 0x91);
+    cpu.inter.tick(456 / 4);
+
+    assert_eq!(cpu.inter.read_byte(0xFF44), 0);
+
+    cpu.inter.tick(456 / 4);
+    assert_eq!(cpu.inter.read_byte(0xFF44), 1);
+}
+
+#[test]
+fn stop_without_key1_stays_stopped() {
+    let mut cpu = setup_cpu(&[0x10, 0x00, 0x00]);
+
+    cpu.step();
+
+    assert!(cpu.stopped);
+    assert_eq!(cpu.inter.read_byte(0xFF4D), 0x7E);
+}
+
+#[test]
+fn switch_pause_is_8200_dots_both_ways_and_freezes_div() {
+    // STOP, 0x00, STOP, 0x00
+    let mut cpu = setup_cpu(&[0x10, 0x00, 0x10, 0x00]);
+    cpu.inter.write_byte(0xFF40, 0x00);
+    cpu.inter.write_byte(0xFF40, 0x91);
+
+    cpu.inter.write_byte(0xFF4D, 0x01);
+    cpu.step();
+    assert_eq!(cpu.inter.read_byte(0xFF4D), 0xFE);
+    assert_eq!(cpu.inter.read_byte(0xFF44) as u32, 8200 / 456);
+    assert_eq!(cpu.inter.read_byte(0xFF04), 0x00);
+
+    cpu.inter.write_byte(0xFF4D, 0x01);
+    cpu.step();
+    assert_eq!(cpu.inter.read_byte(0xFF4D), 0x7E);
+    assert_eq!(cpu.inter.read_byte(0xFF44) as u32, 2 * 8200 / 456);
+    assert_eq!(cpu.inter.read_byte(0xFF04), 0x00);
+}
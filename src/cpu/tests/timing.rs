@@ -0,0 +1,136 @@
+use crate::cart::Cart;
+use crate::cpu::{Cpu, Reg16, Reg8};
+use crate::interconnect::Interconnect;
+use crate::interrupts::Interrupt;
+use crate::model::Model;
+use crate::ppu::Renderer;
+
+fn setup_cpu(program: &[u8]) -> Cpu {
+    let mut rom = vec![0; 0x8000];
+    rom[..program.len()].copy_from_slice(program);
+
+    let mut cpu = Cpu::new(Interconnect::new(
+        Cart::from_bytes(&rom).unwrap(),
+        Model::Dmg,
+    ));
+    cpu.regs.pc = 0x0000;
+    cpu.regs.set16(Reg16::BC, 0xC000);
+    cpu.inter.write_byte(0xFF04, 0x00);
+    cpu
+}
+
+#[test]
+fn instructions_take_their_table_cycles_once() {
+    // NOP, LD A,0x12, LD (BC),A
+    let mut cpu = setup_cpu(&[0x00, 0x3E, 0x12, 0x02]);
+
+    cpu.step();
+    assert_eq!(cpu.cycles, 1);
+    cpu.step();
+    assert_eq!(cpu.cycles, 3);
+    cpu.step();
+    assert_eq!(cpu.cycles, 5);
+}
+
+#[test]
+fn div_advances_once_per_64_nops() {
+    let mut cpu = setup_cpu(&[0x00; 0x100]);
+
+    for _ in 0..63 {
+        cpu.step();
+    }
+    assert_eq!(cpu.inter.read_byte(0xFF04), 0x00);
+
+    cpu.step();
+    assert_eq!(cpu.inter.read_byte(0xFF04), 0x01);
+}
+
+// TIMA overflows on the 4th M-cycle after DIV is reset with TAC = 0x05, and is
+// reloaded from TMA on the 5th
+fn setup_overflow(program: &[u8]) -> Cpu {
+    let mut cpu = setup_cpu(program);
+    cpu.regs.set16(Reg16::HL, 0xFF05);
+    cpu.regs.set8(Reg8::A, 0x99);
+    cpu.inter.write_byte(0xFF05, 0xFF);
+    cpu.inter.write_byte(0xFF06, 0x42);
+    cpu.inter.write_byte(0xFF07, 0x05);
+    cpu.inter.write_byte(0xFF0F, 0x00);
+    cpu.inter.write_byte(0xFF04, 0x00);
+    cpu
+}
+
+#[test]
+fn tima_write_in_reload_cycle_is_ignored() {
+    // NOP x3, LD (HL),A writing in the 5th M-cycle
+    let mut cpu = setup_overflow(&[0x00, 0x00, 0x00, 0x77]);
+
+    for _ in 0..4 {
+        cpu.step();
+    }
+
+    assert_eq!(cpu.inter.read_byte(0xFF05), 0x42);
+    assert_ne!(cpu.inter.interrupts.flag & Interrupt::Timer.bit(), 0);
+}
+
+#[test]
+fn tima_write_in_overflow_cycle_cancels_reload() {
+    // NOP x2, LD (HL),A writing in the 4th M-cycle, NOP
+    let mut cpu = setup_overflow(&[0x00, 0x00, 0x77, 0x00]);
+
+    for _ in 0..4 {
+        cpu.step();
+    }
+
+    assert_eq!(cpu.inter.read_byte(0xFF05), 0x99);
+    assert_eq!(cpu.inter.interrupts.flag & Interrupt::Timer.bit(), 0);
+}
+
+#[test]
+fn bgp_write_lands_at_its_m_cycle() {
+    // NOP x40, then LD (HL),A writes BGP in M-cycle 42, dot 168 of the line
+    let mut program = vec![0x00; 0x200];
+    program[40] = 0x77;
+    let mut cpu = setup_cpu(&program);
+    cpu.inter.ppu.set_renderer(Renderer::Fifo);
+    cpu.regs.set16(Reg16::HL, 0xFF47);
+    cpu.regs.set8(Reg8::A, 0x00);
+
+    // Every tile colour 3, black until BGP is cleared
+    cpu.inter.write_byte(0xFF40, 0x00);
+    for i in 0..16 {
+        cpu.inter.write_byte(0x8000 + i, 0xFF);
+    }
+    cpu.inter.write_byte(0xFF47, 0xE4);
+    cpu.inter.write_byte(0xFF40, 0x91);
+
+    while cpu.inter.read_byte(0xFF44) == 0 {
+        cpu.step();
+    }
+
+    // Pixel 0 is shifted out on dot 93: 80 for OAM scan, 12 for the discarded and first tile fetches
+    let line = &cpu.framebuffer()[..160];
+    assert!(line[..76].iter().all(|&pixel| pixel == line[0]));
+    assert!(line[76..].iter().all(|&pixel| pixel != line[0]));
+}
+
+#[test]
+fn vram_read_sees_the_mode_at_its_m_cycle() {
+    for (nops, expected) in [(17, 0x12), (18, 0xFF), (60, 0xFF), (61, 0x12)] {
+        // NOPs, then LD A,(HL) reads VRAM in M-cycle nops + 2
+        let mut program = vec![0x00; 0x100];
+        program[nops] = 0x7E;
+        let mut cpu = setup_cpu(&program);
+        cpu.regs.set16(Reg16::HL, 0x8000);
+
+        cpu.inter.write_byte(0xFF40, 0x00);
+        cpu.inter.write_byte(0x8000, 0x12);
+        cpu.inter.write_byte(0xFF40, 0x91);
+
+        for _ in 0..=nops {
+            cpu.step();
+        }
+
+        // Mode 3 runs from dot 80 to 252, the ends of M-cycles 20 and 63
+        assert_eq!(cpu.regs.get8(Reg8::A), expected, "{nops} NOPs");
+    }
+}
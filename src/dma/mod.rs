@@ -0,0 +1,67 @@
+//0xFF46 DMA  Source address / 0x100, writing starts a 160 byte copy into OAM, one byte per M-cycle
+
+pub const OAM_SIZE: u16 = 0xA0;
+
+pub struct OamDma {
+    register: u8,
+    source: u16,
+    index: u16,
+    active: bool,
+
+    // A write takes a cycle to start, a transfer already running carries on meanwhile
+    starting: bool,
+}
+
+impl OamDma {
+    pub fn new() -> Self {
+        Self {
+            register: 0xFF,
+            source: 0,
+            index: 0,
+            active: false,
+            starting: false,
+        }
+    }
+
+    /// True while a transfer has the bus.
+    pub fn active(&self) -> bool {
+        self.active
+    }
+
+    pub fn read_register(&self) -> u8 {
+        self.register
+    }
+
+    /// Set FF46 without starting a transfer, for starting without a boot ROM.
+    pub fn set_register(&mut self, value: u8) {
+        self.register = value;
+    }
+
+    pub fn write_register(&mut self, value: u8) {
+        self.register = value;
+        self.starting = true;
+    }
+
+    /// Advance one M-cycle, returning the source address and OAM index of the byte to copy.
+    pub fn step(&mut self) -> Option<(u16, usize)> {
+        let copy = if self.active {
+            let copy = (self.source + self.index, self.index as usize);
+            self.index += 1;
+            self.active = self.index < OAM_SIZE;
+            Some(copy)
+        } else {
+            None
+        };
+
+        if std::mem::take(&mut self.starting) {
+            self.source = (self.register as u16) << 8;
+            self.index = 0;
+            self.active = true;
+        }
+
+        copy
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,33 @@
+use crate::dma::OamDma;
+
+#[test]
+fn starts_one_cycle_after_write() {
+    let mut dma = OamDma::new();
+    dma.write_register(0xC1);
+    assert_eq!(dma.read_register(), 0xC1);
+
+    assert_eq!(dma.step(), None);
+    assert!(dma.active());
+    assert_eq!(dma.step(), Some((0xC100, 0)));
+
+    for _ in 1..0xA0 {
+        dma.step();
+    }
+    assert!(!dma.active());
+    assert_eq!(dma.step(), None);
+}
+
+#[test]
+fn restart_keeps_old_transfer_running_for_a_cycle() {
+    let mut dma = OamDma::new();
+    dma.write_register(0xC1);
+    for _ in 0..0x11 {
+        dma.step();
+    }
+
+    dma.write_register(0xD0);
+
+    assert_eq!(dma.step(), Some((0xC110, 0x10)));
+    assert!(dma.active());
+    assert_eq!(dma.step(), Some((0xD000, 0)));
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+use sdl2::Sdl;
+
+use crate::frontend::ring_buffer::RingBuffer;
+
+const REQUESTED_RATE: i32 = 48000;
+const DEVICE_SAMPLES: u16 = 1024;
+
+// About 85 ms of stereo audio at 48 kHz
+const BUFFER_SAMPLES: usize = 8192;
+
+// Largest change to the resampling ratio, small enough to be inaudible
+const MAX_RATE_DELTA: f64 = 0.005;
+
+struct Playback {
+    buffer: Arc<RingBuffer>,
+    last: [f32; 2],
+}
+
+impl AudioCallback for Playback {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        let count = self.buffer.pop(out);
+
+        if count >= 2 {
+            self.last = [out[count - 2], out[count - 1]];
+        }
+
+        // On underrun hold the last sample rather than clicking to silence
+        for (i, sample) in out[count..].iter_mut().enumerate() {
+            *sample = self.last[(count + i) % 2];
+        }
+    }
+}
+
+pub struct AudioOutput {
+    device: AudioDevice<Playback>,
+    buffer: Arc<RingBuffer>,
+}
+
+impl AudioOutput {
+    pub fn open(sdl: &Sdl) -> Result<Self, String> {
+        let audio = sdl.audio()?;
+        let buffer = Arc::new(RingBuffer::new(BUFFER_SAMPLES));
+
+        let desired = AudioSpecDesired {
+            freq: Some(REQUESTED_RATE),
+            channels: Some(2),
+            samples: Some(DEVICE_SAMPLES),
+        };
+
+        let device = audio.open_playback(None, &desired, |_| Playback {
+            buffer: Arc::clone(&buffer),
+            last: [0.0; 2],
+        })?;
+        device.resume();
+
+        Ok(Self { device, buffer })
+    }
+
+    pub fn queue(&self, samples: &[f32]) {
+        self.buffer.push(samples);
+    }
+
+    /// Sample rate to generate the next frame at. Video is locked to vsync, so
+    /// instead the resampling ratio is nudged to keep the buffer half full.
+    pub fn sample_rate(&self) -> u32 {
+        let fill = self.buffer.len() as f64 / self.buffer.capacity() as f64;
+        adjusted_rate(self.device.spec().freq as u32, fill)
+    }
+}
+
+/// Below half full produce slightly more samples, above it slightly fewer.
+pub fn adjusted_rate(base_rate: u32, fill: f64) -> u32 {
+    let ratio = 1.0 + MAX_RATE_DELTA * (1.0 - 2.0 * fill.clamp(0.0, 1.0));
+    (base_rate as f64 * ratio).round() as u32
+}
@@ -0,0 +1,199 @@
+mod audio;
+mod ring_buffer;
+mod wav;
+
+use sdl2::controller::GameController;
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::PixelFormatEnum;
+
+use crate::cpu::Cpu;
+use crate::joypad::Button;
+use crate::ppu::{SCREEN_HEIGHT, SCREEN_WIDTH};
+use audio::AudioOutput;
+use wav::WavWriter;
+
+const SCALE: u32 = 4;
+const SAMPLE_RATE: u32 = 48000;
+
+// Rumble requests need a duration, the motor is switched off explicitly
+const RUMBLE_MS: u32 = 10_000;
+
+// Flush battery RAM to disk about every 5 seconds
+const SAVE_INTERVAL_FRAMES: u32 = 300;
+
+fn button_for_key(key: Keycode) -> Option<Button> {
+    match key {
+        Keycode::Right => Some(Button::Right),
+        Keycode::Left => Some(Button::Left),
+        Keycode::Up => Some(Button::Up),
+        Keycode::Down => Some(Button::Down),
+        Keycode::X => Some(Button::A),
+        Keycode::Z => Some(Button::B),
+        Keycode::Backspace => Some(Button::Select),
+        Keycode::Return => Some(Button::Start),
+        _ => None,
+    }
+}
+
+// First attached game controller, used to pass on the cartridge's rumble motor
+fn open_controller(sdl: &sdl2::Sdl) -> Option<GameController> {
+    let subsystem = sdl.game_controller().ok()?;
+    let count = subsystem.num_joysticks().ok()?;
+
+    (0..count)
+        .filter(|&index| subsystem.is_game_controller(index))
+        .find_map(|index| subsystem.open(index).ok())
+}
+
+/// Open an SDL window and run the emulator until it is closed, optionally
+/// running a headless `peer` over the link cable and recording the audio output to `wav_path`.
+pub fn run(
+    cpu: &mut Cpu,
+    mut peer: Option<&mut Cpu>,
+    wav_path: Option<&str>,
+) -> Result<(), String> {
+    cpu.set_sample_rate(SAMPLE_RATE);
+
+    let mut wav = match wav_path {
+        Some(path) => Some(WavWriter::create(path, SAMPLE_RATE).map_err(|e| e.to_string())?),
+        None => None,
+    };
+
+    let sdl = sdl2::init()?;
+    let video = sdl.video()?;
+
+    let audio = match AudioOutput::open(&sdl) {
+        Ok(audio) => Some(audio),
+        Err(e) => {
+            eprintln!("Audio disabled: {}", e);
+            None
+        }
+    };
+
+    let window = video
+        .window(
+            "FerrisBoy",
+            SCREEN_WIDTH as u32 * SCALE,
+            SCREEN_HEIGHT as u32 * SCALE,
+        )
+        .position_centered()
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut canvas = window
+        .into_canvas()
+        .present_vsync()
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let texture_creator = canvas.texture_creator();
+    let mut texture = texture_creator
+        .create_texture_streaming(
+            PixelFormatEnum::ARGB8888,
+            SCREEN_WIDTH as u32,
+            SCREEN_HEIGHT as u32,
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut controller = open_controller(&sdl);
+    let mut event_pump = sdl.event_pump()?;
+    let mut frames = 0u32;
+    let mut sample_rate = SAMPLE_RATE;
+
+    'running: loop {
+        for event in event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => break 'running,
+                Event::KeyDown {
+                    keycode: Some(key),
+                    repeat: false,
+                    ..
+                } => {
+                    if let Some(button) = button_for_key(key) {
+                        cpu.press(button);
+                    }
+                }
+                Event::KeyUp {
+                    keycode: Some(key), ..
+                } => {
+                    if let Some(button) = button_for_key(key) {
+                        cpu.release(button);
+                    }
+                }
+                // Don't leave buttons stuck down when the window loses focus
+                Event::Window {
+                    win_event: WindowEvent::FocusLost,
+                    ..
+                } => cpu.set_buttons(0),
+                _ => {}
+            }
+        }
+
+        match peer.as_deref_mut() {
+            Some(peer) => {
+                cpu.run_linked_frame(peer);
+                peer.drain_audio();
+            }
+            None => cpu.run_frame(),
+        }
+
+        let samples = cpu.drain_audio();
+        // The WAV header has a fixed rate, so the recording is resampled back to it
+        if let Some(wav) = wav.as_mut() {
+            wav.write_samples(&samples, sample_rate)
+                .map_err(|e| e.to_string())?;
+        }
+        if let Some(audio) = audio.as_ref() {
+            audio.queue(&samples);
+            sample_rate = audio.sample_rate();
+            cpu.set_sample_rate(sample_rate);
+        }
+
+        frames = frames.wrapping_add(1);
+        if frames.is_multiple_of(SAVE_INTERVAL_FRAMES) {
+            // A failed periodic save is retried next time and again on exit
+            if let Err(e) = cpu.save_ram() {
+                eprintln!("Failed to save cartridge RAM: {}", e);
+            }
+            if let Some(peer) = peer.as_deref_mut() {
+                if let Err(e) = peer.save_ram() {
+                    eprintln!("Failed to save linked cartridge RAM: {}", e);
+                }
+            }
+        }
+
+        if let Some(motor_on) = cpu.take_rumble_event() {
+            if let Some(controller) = controller.as_mut() {
+                let strength = if motor_on { 0xFFFF } else { 0 };
+                // Not every controller can rumble
+                let _ = controller.set_rumble(strength, strength, RUMBLE_MS);
+            }
+        }
+
+        texture.with_lock(None, |buffer: &mut [u8], pitch: usize| {
+            for (y, row) in cpu.framebuffer().chunks(SCREEN_WIDTH).enumerate() {
+                for (x, pixel) in row.iter().enumerate() {
+                    let offset = y * pitch + x * 4;
+                    buffer[offset..offset + 4].copy_from_slice(&pixel.to_ne_bytes());
+                }
+            }
+        })?;
+
+        canvas.copy(&texture, None, None)?;
+        canvas.present();
+    }
+
+    if let Some(wav) = wav {
+        wav.finish().map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,66 @@
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+/// Single-producer single-consumer sample queue shared between the emulator
+/// thread and the SDL audio callback without locking.
+pub struct RingBuffer {
+    data: Box<[AtomicU32]>,
+    // Monotonic counters, wrapped onto `data` with `mask`
+    read: AtomicUsize,
+    write: AtomicUsize,
+    mask: usize,
+}
+
+impl RingBuffer {
+    /// `capacity` is rounded up to a power of two.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.next_power_of_two();
+
+        Self {
+            data: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+            read: AtomicUsize::new(0),
+            write: AtomicUsize::new(0),
+            mask: capacity - 1,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn len(&self) -> usize {
+        let write = self.write.load(Ordering::Acquire);
+        let read = self.read.load(Ordering::Acquire);
+        write.wrapping_sub(read)
+    }
+
+    /// Producer side. Samples that don't fit are dropped; returns how many were queued.
+    pub fn push(&self, samples: &[f32]) -> usize {
+        let write = self.write.load(Ordering::Relaxed);
+        let read = self.read.load(Ordering::Acquire);
+        let free = self.capacity() - write.wrapping_sub(read);
+        let count = samples.len().min(free);
+
+        for (i, sample) in samples[..count].iter().enumerate() {
+            self.data[write.wrapping_add(i) & self.mask].store(sample.to_bits(), Ordering::Relaxed);
+        }
+
+        self.write
+            .store(write.wrapping_add(count), Ordering::Release);
+        count
+    }
+
+    /// Consumer side. Returns how many samples were written into `out`.
+    pub fn pop(&self, out: &mut [f32]) -> usize {
+        let read = self.read.load(Ordering::Relaxed);
+        let write = self.write.load(Ordering::Acquire);
+        let count = out.len().min(write.wrapping_sub(read));
+
+        for (i, sample) in out[..count].iter_mut().enumerate() {
+            *sample =
+                f32::from_bits(self.data[read.wrapping_add(i) & self.mask].load(Ordering::Relaxed));
+        }
+
+        self.read.store(read.wrapping_add(count), Ordering::Release);
+        count
+    }
+}
@@ -0,0 +1,44 @@
+use crate::frontend::audio::adjusted_rate;
+use crate::frontend::ring_buffer::RingBuffer;
+
+#[test]
+fn ring_buffer_round_trips_samples() {
+    let buffer = RingBuffer::new(8);
+
+    assert_eq!(buffer.push(&[0.1, 0.2, 0.3]), 3);
+    assert_eq!(buffer.len(), 3);
+
+    let mut out = [0.0; 2];
+    assert_eq!(buffer.pop(&mut out), 2);
+    assert_eq!(out, [0.1, 0.2]);
+    assert_eq!(buffer.len(), 1);
+}
+
+#[test]
+fn ring_buffer_drops_when_full() {
+    let buffer = RingBuffer::new(4);
+
+    assert_eq!(buffer.push(&[1.0; 6]), 4);
+    assert_eq!(buffer.push(&[2.0]), 0);
+}
+
+#[test]
+fn ring_buffer_wraps_around() {
+    let buffer = RingBuffer::new(4);
+    let mut out = [0.0; 3];
+
+    buffer.push(&[1.0, 2.0, 3.0]);
+    buffer.pop(&mut out);
+    buffer.push(&[4.0, 5.0, 6.0]);
+
+    assert_eq!(buffer.pop(&mut out), 3);
+    assert_eq!(out, [4.0, 5.0, 6.0]);
+}
+
+#[test]
+fn rate_control_centres_on_half_full() {
+    assert_eq!(adjusted_rate(48000, 0.5), 48000);
+    assert!(adjusted_rate(48000, 0.1) > 48000);
+    assert!(adjusted_rate(48000, 0.9) < 48000);
+    assert_eq!(adjusted_rate(48000, 2.0), adjusted_rate(48000, 1.0));
+}
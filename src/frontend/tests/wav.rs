@@ -0,0 +1,29 @@
+use crate::frontend::wav::Resampler;
+
+#[test]
+fn resampler_passes_through_at_the_output_rate() {
+    let mut resampler = Resampler::new(48000);
+
+    assert_eq!(
+        resampler.resample(&[0.5, -0.5, 1.0, -1.0], 48000),
+        [0.0, 0.0, 0.5, -0.5]
+    );
+    assert_eq!(resampler.resample(&[0.25, 0.25], 48000), [1.0, -1.0]);
+}
+
+#[test]
+fn resampler_keeps_the_output_rate_when_the_source_rate_changes() {
+    let mut resampler = Resampler::new(48000);
+
+    // Twice the output rate gives half the frames, half gives twice as many
+    assert_eq!(resampler.resample(&[0.0; 2 * 96], 96000).len(), 2 * 48);
+    assert_eq!(resampler.resample(&[0.0; 2 * 24], 24000).len(), 2 * 48);
+}
+
+#[test]
+fn resampler_interpolates_between_frames() {
+    let mut resampler = Resampler::new(48000);
+
+    let out = resampler.resample(&[1.0, -1.0, 1.0, -1.0], 24000);
+    assert_eq!(out, [0.0, 0.0, 0.5, -0.5, 1.0, -1.0, 1.0, -1.0]);
+}
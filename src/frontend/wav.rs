@@ -0,0 +1,106 @@
+use std::fs::File;
+use std::io::{BufWriter, Result, Seek, SeekFrom, Write};
+
+/// Converts interleaved stereo samples from a varying source rate to a fixed
+/// output rate, by linear interpolation between neighbouring frames.
+pub struct Resampler {
+    output_rate: u32,
+    // Position of the next output frame, in source frames from `last`
+    position: f64,
+    last: [f32; 2],
+}
+
+impl Resampler {
+    pub fn new(output_rate: u32) -> Self {
+        Self {
+            output_rate,
+            position: 0.0,
+            last: [0.0; 2],
+        }
+    }
+
+    pub fn resample(&mut self, samples: &[f32], source_rate: u32) -> Vec<f32> {
+        let step = source_rate as f64 / self.output_rate as f64;
+        let frame = |i: usize| match i {
+            0 => self.last,
+            i => [samples[2 * i - 2], samples[2 * i - 1]],
+        };
+
+        let frames = samples.len() / 2;
+        let mut out = Vec::new();
+        while self.position < frames as f64 {
+            let i = self.position as usize;
+            let t = (self.position - i as f64) as f32;
+            let (a, b) = (frame(i), frame(i + 1));
+            out.push(a[0] + (b[0] - a[0]) * t);
+            out.push(a[1] + (b[1] - a[1]) * t);
+            self.position += step;
+        }
+
+        self.last = frame(frames);
+        self.position -= frames as f64;
+        out
+    }
+}
+
+/// Writes drained APU samples to a 16-bit stereo PCM WAV file.
+pub struct WavWriter {
+    file: BufWriter<File>,
+    data_bytes: u32,
+    resampler: Resampler,
+}
+
+impl WavWriter {
+    pub fn create(path: &str, sample_rate: u32) -> Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+
+        let channels: u16 = 2;
+        let bits_per_sample: u16 = 16;
+        let block_align = channels * bits_per_sample / 8;
+
+        // Chunk sizes are patched in `finish` once the length is known
+        file.write_all(b"RIFF")?;
+        file.write_all(&0u32.to_le_bytes())?;
+        file.write_all(b"WAVE")?;
+
+        file.write_all(b"fmt ")?;
+        file.write_all(&16u32.to_le_bytes())?;
+        file.write_all(&1u16.to_le_bytes())?; // PCM
+        file.write_all(&channels.to_le_bytes())?;
+        file.write_all(&sample_rate.to_le_bytes())?;
+        file.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        file.write_all(&block_align.to_le_bytes())?;
+        file.write_all(&bits_per_sample.to_le_bytes())?;
+
+        file.write_all(b"data")?;
+        file.write_all(&0u32.to_le_bytes())?;
+
+        Ok(Self {
+            file,
+            data_bytes: 0,
+            resampler: Resampler::new(sample_rate),
+        })
+    }
+
+    /// Write samples generated at `source_rate`, which can change from call to
+    /// call while the file keeps the rate it was created with.
+    pub fn write_samples(&mut self, samples: &[f32], source_rate: u32) -> Result<()> {
+        let samples = self.resampler.resample(samples, source_rate);
+        for &sample in &samples {
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            self.file.write_all(&value.to_le_bytes())?;
+        }
+        self.data_bytes += samples.len() as u32 * 2;
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        self.file.seek(SeekFrom::Start(4))?;
+        self.file.write_all(&(36 + self.data_bytes).to_le_bytes())?;
+
+        self.file.seek(SeekFrom::Start(40))?;
+        self.file.write_all(&self.data_bytes.to_le_bytes())?;
+
+        self.file.flush()
+    }
+}
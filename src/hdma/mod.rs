@@ -0,0 +1,90 @@
+//0xFF51 HDMA1  Source high
+//0xFF52 HDMA2  Source low, bits 0-3 ignored
+//0xFF53 HDMA3  Destination high, bits 5-7 ignored (always VRAM)
+//0xFF54 HDMA4  Destination low, bits 0-3 ignored
+//0xFF55 HDMA5  Write: bit 7 set for HBlank DMA, bits 0-6 blocks - 1. Writing bit 7 clear
+//              during HBlank DMA cancels it. Read: blocks left - 1, bit 7 set when idle
+
+pub const BLOCK_SIZE: u16 = 0x10;
+
+// M-cycles the CPU is halted for each block copied
+pub const BLOCK_CYCLES: u32 = 8;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HdmaMode {
+    /// Copies everything at once
+    General,
+    /// Copies one block at the start of each HBlank
+    HBlank,
+}
+
+pub struct Hdma {
+    source: u16,
+    dest: u16,
+    remaining: u8,
+    mode: Option<HdmaMode>,
+}
+
+impl Hdma {
+    pub fn new() -> Self {
+        Self {
+            source: 0,
+            dest: 0,
+            remaining: 0x7F,
+            mode: None,
+        }
+    }
+
+    pub fn mode(&self) -> Option<HdmaMode> {
+        self.mode
+    }
+
+    pub fn read_register(&self, address: u16) -> u8 {
+        match address {
+            0xFF55 if self.mode.is_some() => self.remaining,
+            0xFF55 => 0x80 | self.remaining,
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0xFF51 => self.source = (self.source & 0x00FF) | (value as u16) << 8,
+            0xFF52 => self.source = (self.source & 0xFF00) | (value & 0xF0) as u16,
+            0xFF53 => self.dest = (self.dest & 0x00FF) | ((value & 0x1F) as u16) << 8,
+            0xFF54 => self.dest = (self.dest & 0xFF00) | (value & 0xF0) as u16,
+            0xFF55 => match self.mode {
+                Some(HdmaMode::HBlank) if value & 0x80 == 0 => self.mode = None,
+                _ => {
+                    self.remaining = value & 0x7F;
+                    self.mode = Some(if value & 0x80 != 0 {
+                        HdmaMode::HBlank
+                    } else {
+                        HdmaMode::General
+                    });
+                }
+            },
+            _ => (),
+        }
+    }
+
+    /// Source and VRAM destination of the next block, advancing past it.
+    pub fn next_block(&mut self) -> (u16, u16) {
+        let block = (self.source, 0x8000 | self.dest);
+
+        self.source = self.source.wrapping_add(BLOCK_SIZE);
+        self.dest = (self.dest + BLOCK_SIZE) & 0x1FF0;
+
+        if self.remaining == 0 {
+            self.remaining = 0x7F;
+            self.mode = None;
+        } else {
+            self.remaining -= 1;
+        }
+
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,42 @@
+use crate::hdma::{Hdma, HdmaMode};
+
+#[test]
+fn idle_reads_ff() {
+    let hdma = Hdma::new();
+
+    assert_eq!(hdma.read_register(0xFF55), 0xFF);
+    assert_eq!(hdma.read_register(0xFF51), 0xFF);
+}
+
+#[test]
+fn blocks_are_aligned_and_advance() {
+    let mut hdma = Hdma::new();
+    hdma.write_register(0xFF51, 0xC1);
+    hdma.write_register(0xFF52, 0x2F);
+    hdma.write_register(0xFF53, 0xE3);
+    hdma.write_register(0xFF54, 0x4F);
+    hdma.write_register(0xFF55, 0x81);
+
+    assert_eq!(hdma.read_register(0xFF55), 0x01);
+    assert_eq!(hdma.next_block(), (0xC120, 0x8340));
+    assert_eq!(hdma.read_register(0xFF55), 0x00);
+    assert_eq!(hdma.next_block(), (0xC130, 0x8350));
+    assert_eq!(hdma.mode(), None);
+    assert_eq!(hdma.read_register(0xFF55), 0xFF);
+}
+
+#[test]
+fn cancelling_hblank_dma_keeps_remaining_length() {
+    let mut hdma = Hdma::new();
+    hdma.write_register(0xFF55, 0x85);
+    hdma.next_block();
+
+    hdma.write_register(0xFF55, 0x00);
+
+    assert_eq!(hdma.mode(), None);
+    assert_eq!(hdma.read_register(0xFF55), 0x84);
+
+    // Writing bit 7 clear while idle starts a general transfer
+    hdma.write_register(0xFF55, 0x00);
+    assert_eq!(hdma.mode(), Some(HdmaMode::General));
+}
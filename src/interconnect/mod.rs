@@ -5,79 +5,413 @@
 //0xC000 - 0xCFFF	4 KiB Work RAM (WRAM)	RAM BANK 0
 //0xD000 - 0xDFFF	4 KiB Work RAM (WRAM)	In CGB mode, switchable bank 1–7
 //0xE000 - 0xFDFF	Echo RAM (mirror of C000–DDFF)	Nintendo says use of this area is prohibited.
-//0xFE00 - 0xFE9F	Object attribute memory (OAM)
+//0xFE00 - 0xFE9F	Object attribute memory (OAM)	Reads 0xFF and ignores writes in PPU modes 2-3, VRAM does in mode 3
 //0xFEA0 - 0xFEFF	Not Usable	Nintendo says use of this area is prohibited.
 //0xFF00 - 0xFF7F	I/O Registers
 //0xFF80 - 0xFFFE	High RAM (HRAM) (zero page)
 //FFFF	FFFF	Interrupt Enable register (IE)
+//0xFF50         Boot ROM disable, any write with bit 0 set unmaps the boot ROM until reset
+//0xFF46 DMA     OAM DMA, only HRAM 0xFF80 - 0xFFFE is reachable by the CPU during the transfer
+//0xFF4D KEY1    Speed switch, bit 7 current speed (read only), bit 0 arms a switch on STOP. CGB mode only
+//0xFF51 - 0xFF55 HDMA1-HDMA5  VRAM DMA, CGB mode only
+//0xFF70 SVBK    WRAM bank for 0xD000 - 0xDFFF, 1-7 (0 selects 1), CGB mode only
+
+use std::io::{Error, ErrorKind, Result};
+
+use crate::apu::Apu;
+use crate::cart::header::CgbSupport;
+use crate::cart::Cart;
+use crate::dma::OamDma;
+use crate::hdma::{Hdma, HdmaMode, BLOCK_CYCLES, BLOCK_SIZE};
+use crate::interrupts::InterruptController;
+use crate::joypad::Joypad;
+use crate::model::Model;
+use crate::ppu::{ColorMode, Ppu};
+use crate::serial::Serial;
+use crate::timer::Timer;
+
+const DEFAULT_SAMPLE_RATE: u32 = 48000;
+
+pub const BOOT_ROM_SIZE: usize = 0x100;
+
+const WRAM_BANK_SIZE: usize = 0x1000;
+
+// Time for the CGB clock to settle after a speed switch, the same at either speed
+const SPEED_SWITCH_DOTS: u32 = 8200;
+
+// I/O registers as the boot ROMs leave them, in write order so the APU is powered on first
+const POST_BOOT_IO: [(u16, u8); 31] = [
+    (0xFF00, 0xCF), // P1
+    (0xFF01, 0x00), // SB
+    (0xFF02, 0x7E), // SC
+    (0xFF05, 0x00), // TIMA
+    (0xFF06, 0x00), // TMA
+    (0xFF07, 0xF8), // TAC
+    (0xFF26, 0xF1), // NR52
+    (0xFF10, 0x80), // NR10
+    (0xFF11, 0xBF), // NR11
+    (0xFF12, 0xF3), // NR12
+    (0xFF13, 0xFF), // NR13
+    (0xFF16, 0x3F), // NR21
+    (0xFF17, 0x00), // NR22
+    (0xFF18, 0xFF), // NR23
+    (0xFF19, 0xBF), // NR24
+    (0xFF1A, 0x7F), // NR30
+    (0xFF1B, 0xFF), // NR31
+    (0xFF1C, 0x9F), // NR32
+    (0xFF1D, 0xFF), // NR33
+    (0xFF1E, 0xBF), // NR34
+    (0xFF20, 0xFF), // NR41
+    (0xFF21, 0x00), // NR42
+    (0xFF22, 0x00), // NR43
+    (0xFF23, 0xBF), // NR44
+    (0xFF24, 0x77), // NR50
+    (0xFF25, 0xF3), // NR51
+    (0xFF40, 0x91), // LCDC
+    (0xFF47, 0xFC), // BGP
+    (0xFF48, 0xFF), // OBP0
+    (0xFF49, 0xFF), // OBP1
+    (0xFF0F, 0xE1), // IF, VBlank still pending from the logo
+];
+
+// Per-model values, written after the shared table and overriding it. The CGB
+// registers that aren't backed by a component read 0xFF on the DMG and SGB
+const DMG_POST_BOOT_IO: [(u16, u8); 10] = [
+    (0xFF14, 0xBF), // NR14, leaves channel 1 running
+    (0xFF46, 0xFF), // DMA
+    (0xFF4D, 0xFF), // KEY1
+    (0xFF51, 0xFF), // HDMA1
+    (0xFF52, 0xFF), // HDMA2
+    (0xFF53, 0xFF), // HDMA3
+    (0xFF54, 0xFF), // HDMA4
+    (0xFF55, 0xFF), // HDMA5
+    (0xFF56, 0xFF), // RP
+    (0xFF70, 0xFF), // SVBK
+];
+
+const SGB_POST_BOOT_IO: [(u16, u8); 10] = [
+    (0xFF14, 0x3F), // NR14, the SGB boot ROM doesn't play the chime
+    (0xFF46, 0xFF), // DMA
+    (0xFF4D, 0xFF), // KEY1
+    (0xFF51, 0xFF), // HDMA1
+    (0xFF52, 0xFF), // HDMA2
+    (0xFF53, 0xFF), // HDMA3
+    (0xFF54, 0xFF), // HDMA4
+    (0xFF55, 0xFF), // HDMA5
+    (0xFF56, 0xFF), // RP
+    (0xFF70, 0xFF), // SVBK
+];
+
+// HDMA5 is left out, writing it would start a transfer and it already reads 0xFF when idle
+const CGB_POST_BOOT_IO: [(u16, u8); 7] = [
+    (0xFF14, 0xBF), // NR14, leaves channel 1 running
+    (0xFF02, 0x7F), // SC
+    (0xFF46, 0x00), // DMA
+    (0xFF4D, 0x7E), // KEY1
+    (0xFF4F, 0xFE), // VBK
+    (0xFF56, 0x3E), // RP
+    (0xFF70, 0xF8), // SVBK
+];
 
 pub struct Interconnect {
-    pub rom: [u8; 0x8000],
-    pub vram: [u8; 0x2000],
-    pub wram: [u8; 0x2000],
-    pub oam: [u8; 0xA0],
+    pub model: Model,
+    pub cart: Cart,
+    // Overlays 0x0000-0x00FF while mapped
+    boot_rom: Option<Vec<u8>>,
+    pub ppu: Ppu,
+    pub timer: Timer,
+    pub joypad: Joypad,
+    pub serial: Serial,
+    pub apu: Apu,
+    pub oam_dma: OamDma,
+    pub hdma: Hdma,
+    // M-cycles the CPU still has to sit out for VRAM DMA
+    dma_stall: u32,
+    double_speed: bool,
+    speed_switch_armed: bool,
+    // Eight 4 KiB banks, only banks 0 and 1 are reachable outside CGB mode
+    pub wram: [u8; WRAM_BANK_SIZE * 8],
+    wram_bank: usize,
     pub io: [u8; 0x80],
     pub hram: [u8; 0x7F],
-    pub ie_register: u8,
+    pub interrupts: InterruptController,
 }
 
 impl Interconnect {
-    /// Initialize with a test Vec<u8> memory
-    pub fn new(memory: Vec<u8>) -> Self {
-        let mut inter = Self {
-            rom: [0; 0x8000],
-            vram: [0; 0x2000],
-            wram: [0; 0x2000],
-            oam: [0; 0xA0],
+    pub fn new(cart: Cart, model: Model) -> Self {
+        let color_mode = match cart.rom_head.cgb_support {
+            _ if !model.is_cgb() => ColorMode::Dmg,
+            CgbSupport::None => ColorMode::DmgCompat,
+            CgbSupport::Enhanced | CgbSupport::Only => ColorMode::Cgb,
+        };
+
+        Self {
+            model,
+            cart,
+            boot_rom: None,
+            ppu: Ppu::new(color_mode),
+            timer: Timer::new(),
+            joypad: Joypad::new(),
+            serial: Serial::new(),
+            apu: Apu::new(DEFAULT_SAMPLE_RATE),
+            oam_dma: OamDma::new(),
+            hdma: Hdma::new(),
+            dma_stall: 0,
+            double_speed: false,
+            speed_switch_armed: false,
+            wram: [0; WRAM_BANK_SIZE * 8],
+            wram_bank: 1,
             io: [0; 0x80],
             hram: [0; 0x7F],
-            ie_register: 0,
+            interrupts: InterruptController::new(),
+        }
+    }
+
+    /// Map a DMG boot ROM over the start of the cartridge until the boot ROM writes FF50.
+    pub fn load_boot_rom(&mut self, rom: Vec<u8>) -> Result<()> {
+        if rom.len() != BOOT_ROM_SIZE {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "boot ROM is {} bytes, expected {}",
+                    rom.len(),
+                    BOOT_ROM_SIZE
+                ),
+            ));
+        }
+
+        self.boot_rom = Some(rom);
+        Ok(())
+    }
+
+    /// True when running a CGB cartridge on a CGB, rather than in DMG compatibility mode.
+    pub fn cgb_mode(&self) -> bool {
+        self.ppu.color_mode() == ColorMode::Cgb
+    }
+
+    pub fn boot_rom_mapped(&self) -> bool {
+        self.boot_rom.is_some()
+    }
+
+    /// Put the I/O registers in the state the boot ROM would have left them in.
+    pub fn skip_boot_rom(&mut self) {
+        let model_io: &[(u16, u8)] = if self.model.is_cgb() {
+            &CGB_POST_BOOT_IO
+        } else if self.model.is_sgb() {
+            &SGB_POST_BOOT_IO
+        } else {
+            &DMG_POST_BOOT_IO
         };
 
-        for (addr, &val) in memory.iter().enumerate() {
-            let address = addr as u16;
+        for &(address, value) in POST_BOOT_IO.iter().chain(model_io) {
             match address {
-                0x0000..=0x7FFF => inter.rom[address as usize] = val,
-                0x8000..=0x9FFF => inter.vram[(address - 0x8000) as usize] = val,
-                0xC000..=0xDFFF => inter.wram[(address - 0xC000) as usize] = val,
-                0xFE00..=0xFE9F => inter.oam[(address - 0xFE00) as usize] = val,
-                0xFF00..=0xFF7F => inter.io[(address - 0xFF00) as usize] = val,
-                0xFF80..=0xFFFE => inter.hram[(address - 0xFF80) as usize] = val,
-                0xFFFF => inter.ie_register = val,
-                _ => {}
+                // Writing FF46 would start a transfer
+                0xFF46 => self.oam_dma.set_register(value),
+                _ => self.write_byte(address, value),
             }
         }
-        inter
+
+        self.timer.set_counter(self.model.post_boot_div());
+        self.boot_rom = None;
     }
 
     pub fn read_byte(&mut self, address: u16) -> u8 {
+        if self.oam_dma_blocks(address) || self.ppu_blocks(address) {
+            return 0xFF;
+        }
+        self.read_bus(address)
+    }
+
+    pub fn write_byte(&mut self, address: u16, value: u8) {
+        if !self.oam_dma_blocks(address) && !self.ppu_blocks(address) {
+            self.write_bus(address, value);
+        }
+    }
+
+    // OAM DMA holds the bus, the CPU only keeps HRAM to run its wait loop from
+    fn oam_dma_blocks(&self, address: u16) -> bool {
+        self.oam_dma.active() && !(0xFF80..=0xFFFE).contains(&address)
+    }
+
+    // Only the CPU is locked out by the PPU mode, both DMA engines use read_bus directly
+    fn ppu_blocks(&self, address: u16) -> bool {
+        match address {
+            0x8000..=0x9FFF => !self.ppu.vram_accessible(),
+            0xFE00..=0xFE9F => !self.ppu.oam_accessible(),
+            _ => false,
+        }
+    }
+
+    fn read_bus(&mut self, address: u16) -> u8 {
+        if let Some(boot_rom) = &self.boot_rom {
+            if (address as usize) < BOOT_ROM_SIZE {
+                return boot_rom[address as usize];
+            }
+        }
+
         match address {
-            0x0000..=0x7FFF => self.rom[address as usize],
-            0x8000..=0x9FFF => self.vram[(address - 0x8000) as usize],
-            0xC000..=0xDFFF => self.wram[(address - 0xC000) as usize],
-            0xFE00..=0xFE9F => self.oam[(address - 0xFE00) as usize],
-            0xFF00..=0xFF7F => self.io[(address - 0xFF00) as usize],
+            0x0000..=0x7FFF => self.cart.read_rom(address),
+            0x8000..=0x9FFF => self.ppu.read_vram(address),
+            0xA000..=0xBFFF => self.cart.read_ram(address),
+            0xC000..=0xDFFF => self.wram[self.wram_offset(address)],
+            0xFE00..=0xFE9F => self.ppu.oam[(address - 0xFE00) as usize],
+            0xFF00 => self.joypad.read_register(),
+            0xFF01..=0xFF02 => self.serial.read_register(address),
+            0xFF04..=0xFF07 => self.timer.read_register(address),
+            0xFF0F => self.interrupts.read_flag(),
+            0xFF10..=0xFF3F => self.apu.read_register(address),
+            0xFF40..=0xFF45 | 0xFF47..=0xFF4B | 0xFF4F | 0xFF68..=0xFF6B => {
+                self.ppu.read_register(address)
+            }
+            0xFF46 => self.oam_dma.read_register(),
+            0xFF4D if self.cgb_mode() => {
+                0x7E | (self.double_speed as u8) << 7 | self.speed_switch_armed as u8
+            }
+            0xFF50 => 0xFF,
+            0xFF51..=0xFF55 if self.cgb_mode() => self.hdma.read_register(address),
+            0xFF70 if self.cgb_mode() => 0xF8 | self.wram_bank as u8,
+            0xFF03..=0xFF7F => self.io[(address - 0xFF00) as usize],
             0xFF80..=0xFFFE => self.hram[(address - 0xFF80) as usize],
-            0xFFFF => self.ie_register,
+            0xFFFF => self.interrupts.enable,
 
             _ => 0xFF,
         }
     }
 
-    pub fn write_byte(&mut self, address: u16, value: u8) {
+    fn write_bus(&mut self, address: u16, value: u8) {
         match address {
-            0x0000..=0x7FFF => self.rom[address as usize] = value,
-            0x8000..=0x9FFF => self.vram[(address - 0x8000) as usize] = value,
-            0xC000..=0xDFFF => self.wram[(address - 0xC000) as usize] = value,
-            0xFE00..=0xFE9F => self.oam[(address - 0xFE00) as usize] = value,
-            0xFF00..=0xFF7F => self.io[(address - 0xFF00) as usize] = value,
+            0x0000..=0x7FFF => self.cart.write_rom(address, value),
+            0x8000..=0x9FFF => self.ppu.write_vram(address, value),
+            0xA000..=0xBFFF => self.cart.write_ram(address, value),
+            0xC000..=0xDFFF => self.wram[self.wram_offset(address)] = value,
+            0xFE00..=0xFE9F => self.ppu.oam[(address - 0xFE00) as usize] = value,
+            0xFF00 => self.joypad.write_register(value, &mut self.interrupts),
+            0xFF01..=0xFF02 => self.serial.write_register(address, value),
+            0xFF04..=0xFF07 => self.timer.write_register(address, value),
+            0xFF0F => self.interrupts.write_flag(value),
+            0xFF10..=0xFF3F => self.apu.write_register(address, value),
+            0xFF40..=0xFF45 | 0xFF47..=0xFF4B | 0xFF4F | 0xFF68..=0xFF6B => self
+                .ppu
+                .write_register(address, value, &mut self.interrupts),
+            0xFF46 => self.oam_dma.write_register(value),
+            0xFF4D if self.cgb_mode() => self.speed_switch_armed = value & 0x01 != 0,
+            0xFF50 if value & 0x01 != 0 => self.boot_rom = None,
+            0xFF51..=0xFF55 if self.cgb_mode() => self.write_hdma(address, value),
+            0xFF70 if self.cgb_mode() => self.wram_bank = ((value & 0x07) as usize).max(1),
+            0xFF03..=0xFF7F => self.io[(address - 0xFF00) as usize] = value,
             0xFF80..=0xFFFE => self.hram[(address - 0xFF80) as usize] = value,
-            0xFFFF => self.ie_register = value,
+            0xFFFF => self.interrupts.enable = value,
 
             _ => (),
         }
     }
 
+    fn write_hdma(&mut self, address: u16, value: u8) {
+        self.hdma.write_register(address, value);
+        if address != 0xFF55 {
+            return;
+        }
+
+        match self.hdma.mode() {
+            Some(HdmaMode::General) => {
+                while self.hdma.mode().is_some() {
+                    self.copy_hdma_block();
+                }
+            }
+            // With the LCD off there is no HBlank, the first block is copied straight away
+            Some(HdmaMode::HBlank) if !self.ppu.lcd_enabled() => self.copy_hdma_block(),
+            _ => (),
+        }
+    }
+
+    // A long tick can cover several HBlanks, each one gets its block
+    fn run_hblank_dma(&mut self) {
+        for _ in 0..self.ppu.take_hblank_starts() {
+            if self.hdma.mode() == Some(HdmaMode::HBlank) {
+                self.copy_hdma_block();
+            }
+        }
+    }
+
+    fn copy_hdma_block(&mut self) {
+        let (source, dest) = self.hdma.next_block();
+        for i in 0..BLOCK_SIZE {
+            let value = self.read_bus(source.wrapping_add(i));
+            self.ppu.write_vram(dest + i, value);
+        }
+        // The copy takes the same time at either speed, so twice the CPU cycles in double speed
+        self.dma_stall += BLOCK_CYCLES << self.double_speed as u32;
+    }
+
+    pub fn double_speed(&self) -> bool {
+        self.double_speed
+    }
+
+    /// Called on STOP. Switches CPU speed if KEY1 armed it, returning whether it did.
+    pub fn switch_speed(&mut self) -> bool {
+        if !self.speed_switch_armed {
+            return false;
+        }
+
+        self.speed_switch_armed = false;
+        self.double_speed = !self.double_speed;
+        self.timer.set_double_speed(self.double_speed);
+
+        // The CPU and timer are stopped while the clock settles, the PPU and APU keep going
+        self.ppu.tick(SPEED_SWITCH_DOTS, &mut self.interrupts);
+        self.run_hblank_dma();
+        self.apu.tick(SPEED_SWITCH_DOTS, 0);
+        true
+    }
+
+    /// M-cycles the CPU is halted for by VRAM DMA since the last call.
+    pub fn take_dma_stall(&mut self) -> u32 {
+        std::mem::take(&mut self.dma_stall)
+    }
+
+    fn wram_offset(&self, address: u16) -> usize {
+        match address {
+            0xC000..=0xCFFF => (address - 0xC000) as usize,
+            _ => self.wram_bank * WRAM_BANK_SIZE + (address - 0xD000) as usize,
+        }
+    }
+
+    /// Clock the peripherals by `cycles` machine cycles. The timer and serial port run
+    /// off the CPU clock, the PPU and APU at a fixed 4 MiHz whatever the CPU speed.
+    pub fn tick(&mut self, cycles: u32) {
+        let dots = if self.double_speed {
+            cycles * 2
+        } else {
+            cycles * 4
+        };
+
+        for _ in 0..cycles {
+            if let Some((source, index)) = self.oam_dma.step() {
+                // Sources above 0xDFFF see the echo of WRAM
+                let source = if source >= 0xE000 {
+                    source - 0x2000
+                } else {
+                    source
+                };
+                self.ppu.oam[index] = self.read_bus(source);
+            }
+        }
+
+        self.timer.tick(cycles, &mut self.interrupts);
+        self.serial.tick(cycles, &mut self.interrupts);
+        self.ppu.tick(dots, &mut self.interrupts);
+
+        self.run_hblank_dma();
+
+        let frame_sequencer_clocks = self.timer.take_frame_sequencer_clocks();
+        self.apu.tick(dots, frame_sequencer_clocks);
+    }
+
+    /// STOP resets the divider.
+    pub fn reset_div(&mut self) {
+        self.timer.reset_div();
+    }
+
     // pub fn fetch_byte(&self, mem_addr: u16) -> u8
     // {
 
@@ -90,3 +424,6 @@ impl Interconnect {
 
     // }
 }
+
+#[cfg(test)]
+mod tests;
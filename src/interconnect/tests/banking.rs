@@ -0,0 +1,47 @@
+use crate::cart::Cart;
+use crate::interconnect::Interconnect;
+use crate::model::Model;
+
+fn setup_interconnect(cgb_flag: u8, model: Model) -> Interconnect {
+    let mut rom = vec![0; 0x8000];
+    rom[0x0143] = cgb_flag;
+
+    Interconnect::new(Cart::from_bytes(&rom).unwrap(), model)
+}
+
+#[test]
+fn cgb_mode_switches_vram_and_wram_banks() {
+    let mut inter = setup_interconnect(0x80, Model::Cgb);
+    assert!(inter.cgb_mode());
+
+    inter.write_byte(0x8000, 0x11);
+    inter.write_byte(0xFF4F, 0x01);
+    inter.write_byte(0x8000, 0x22);
+    assert_eq!(inter.read_byte(0xFF4F), 0xFF);
+    assert_eq!(inter.read_byte(0x8000), 0x22);
+    inter.write_byte(0xFF4F, 0x00);
+    assert_eq!(inter.read_byte(0x8000), 0x11);
+
+    for bank in 1..8 {
+        inter.write_byte(0xFF70, bank);
+        inter.write_byte(0xD000, bank);
+    }
+    inter.write_byte(0xFF70, 0x00);
+    assert_eq!(inter.read_byte(0xFF70), 0xF9);
+    assert_eq!(inter.read_byte(0xD000), 1);
+    inter.write_byte(0xFF70, 0x05);
+    assert_eq!(inter.read_byte(0xD000), 5);
+}
+
+#[test]
+fn dmg_cart_on_cgb_ignores_bank_registers() {
+    let mut inter = setup_interconnect(0x00, Model::Cgb);
+    assert!(!inter.cgb_mode());
+
+    inter.write_byte(0xD000, 0x33);
+    inter.write_byte(0xFF70, 0x03);
+    inter.write_byte(0xFF4F, 0x01);
+
+    assert_eq!(inter.read_byte(0xD000), 0x33);
+    assert_eq!(inter.read_byte(0xFF4F), 0xFF);
+}
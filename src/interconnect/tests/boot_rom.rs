@@ -0,0 +1,37 @@
+use crate::cart::Cart;
+use crate::interconnect::Interconnect;
+use crate::model::Model;
+
+fn setup_interconnect() -> Interconnect {
+    let mut rom = vec![0; 0x8000];
+    rom[0x0000] = 0xCA;
+    rom[0x0100] = 0xFE;
+
+    Interconnect::new(Cart::from_bytes(&rom).unwrap(), Model::Dmg)
+}
+
+#[test]
+fn boot_rom_overlays_until_ff50_write() {
+    let mut inter = setup_interconnect();
+    inter.load_boot_rom(vec![0x31; 0x100]).unwrap();
+
+    assert_eq!(inter.read_byte(0x0000), 0x31);
+    assert_eq!(inter.read_byte(0x00FF), 0x31);
+    assert_eq!(inter.read_byte(0x0100), 0xFE);
+
+    // Bit 0 clear doesn't unmap
+    inter.write_byte(0xFF50, 0x00);
+    assert_eq!(inter.read_byte(0x0000), 0x31);
+
+    inter.write_byte(0xFF50, 0x01);
+    assert_eq!(inter.read_byte(0x0000), 0xCA);
+    assert!(!inter.boot_rom_mapped());
+}
+
+#[test]
+fn boot_rom_must_be_256_bytes() {
+    let mut inter = setup_interconnect();
+
+    assert!(inter.load_boot_rom(vec![0; 0x800]).is_err());
+    assert!(!inter.boot_rom_mapped());
+}
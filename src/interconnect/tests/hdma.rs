@@ -0,0 +1,65 @@
+use crate::cart::Cart;
+use crate::interconnect::Interconnect;
+use crate::model::Model;
+
+fn setup_interconnect() -> Interconnect {
+    let mut rom = vec![0; 0x8000];
+    rom[0x0143] = 0x80;
+
+    let mut inter = Interconnect::new(Cart::from_bytes(&rom).unwrap(), Model::Cgb);
+    for i in 0..0x40 {
+        inter.write_byte(0xC000 + i, i as u8 + 1);
+    }
+    inter.write_byte(0xFF51, 0xC0);
+    inter.write_byte(0xFF52, 0x00);
+    inter.write_byte(0xFF53, 0x01);
+    inter.write_byte(0xFF54, 0x00);
+    inter
+}
+
+#[test]
+fn general_dma_copies_everything_and_stalls() {
+    let mut inter = setup_interconnect();
+
+    inter.write_byte(0xFF55, 0x03);
+
+    assert_eq!(inter.read_byte(0x8100), 0x01);
+    assert_eq!(inter.read_byte(0x813F), 0x40);
+    assert_eq!(inter.read_byte(0xFF55), 0xFF);
+    assert_eq!(inter.take_dma_stall(), 4 * 8);
+}
+
+#[test]
+fn hblank_dma_copies_a_block_per_hblank() {
+    let mut inter = setup_interconnect();
+    inter.write_byte(0xFF40, 0x91);
+
+    inter.write_byte(0xFF55, 0x81);
+    assert_eq!(inter.read_byte(0x8100), 0x00);
+
+    // OAM scan and pixel transfer of line 0
+    inter.tick((80 + 172) / 4);
+    assert_eq!(inter.read_byte(0x8100), 0x01);
+    assert_eq!(inter.read_byte(0x8110), 0x00);
+    assert_eq!(inter.read_byte(0xFF55), 0x00);
+    assert_eq!(inter.take_dma_stall(), 8);
+
+    inter.tick(456 / 4);
+    assert_eq!(inter.read_byte(0x811F), 0x20);
+    assert_eq!(inter.read_byte(0xFF55), 0xFF);
+}
+
+#[test]
+fn hblank_dma_copies_a_block_for_every_hblank_in_one_tick() {
+    let mut inter = setup_interconnect();
+    inter.write_byte(0xFF40, 0x91);
+
+    inter.write_byte(0xFF55, 0x83);
+
+    // Line 0 up to HBlank, then three more lines
+    inter.tick((80 + 172 + 3 * 456) / 4);
+    assert_eq!(inter.read_byte(0x8100), 0x01);
+    assert_eq!(inter.read_byte(0x813F), 0x40);
+    assert_eq!(inter.read_byte(0xFF55), 0xFF);
+    assert_eq!(inter.take_dma_stall(), 4 * 8);
+}
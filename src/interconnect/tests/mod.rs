@@ -0,0 +1,6 @@
+mod banking;
+mod boot_rom;
+mod hdma;
+mod oam_dma;
+mod post_boot;
+mod ppu_access;
@@ -0,0 +1,45 @@
+use crate::cart::Cart;
+use crate::interconnect::Interconnect;
+use crate::model::Model;
+
+fn setup_interconnect() -> Interconnect {
+    let mut inter = Interconnect::new(Cart::from_bytes(&[0; 0x8000]).unwrap(), Model::Dmg);
+    for i in 0..0xA0 {
+        inter.write_byte(0xC000 + i, i as u8);
+    }
+    inter
+}
+
+#[test]
+fn copies_to_oam_over_160_cycles() {
+    let mut inter = setup_interconnect();
+
+    inter.write_byte(0xFF46, 0xC0);
+    inter.tick(1 + 0x9F);
+    assert_eq!(inter.ppu.oam[0x9E], 0x9E);
+    assert_eq!(inter.ppu.oam[0x9F], 0x00);
+
+    inter.tick(1);
+    assert_eq!(inter.ppu.oam[0x9F], 0x9F);
+    assert_eq!(inter.read_byte(0xFE10), 0x10);
+}
+
+#[test]
+fn cpu_only_reaches_hram_during_transfer() {
+    let mut inter = setup_interconnect();
+    inter.write_byte(0xFF80, 0x42);
+    inter.write_byte(0xFFFF, 0x01);
+
+    inter.write_byte(0xFF46, 0xC0);
+    inter.tick(2);
+
+    assert_eq!(inter.read_byte(0xC005), 0xFF);
+    assert_eq!(inter.read_byte(0x0000), 0xFF);
+    assert_eq!(inter.read_byte(0xFF80), 0x42);
+    assert_eq!(inter.read_byte(0xFF46), 0xFF);
+    assert_eq!(inter.read_byte(0xFFFF), 0xFF);
+
+    inter.write_byte(0xC005, 0x99);
+    inter.tick(0xA0);
+    assert_eq!(inter.read_byte(0xC005), 0x05);
+}
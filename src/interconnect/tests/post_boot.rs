@@ -0,0 +1,121 @@
+use crate::cart::Cart;
+use crate::interconnect::Interconnect;
+use crate::model::Model;
+
+// Skip the boot ROM on `model` with a CGB-enhanced cart and check `registers`
+fn check_post_boot(model: Model, registers: &[(u16, u8)]) {
+    let mut rom = vec![0; 0x8000];
+    rom[0x0143] = 0x80;
+
+    let mut inter = Interconnect::new(Cart::from_bytes(&rom).unwrap(), model);
+    inter.skip_boot_rom();
+
+    for &(address, value) in registers {
+        assert_eq!(
+            inter.read_byte(address),
+            value,
+            "{:?} {:#06X}",
+            model,
+            address
+        );
+    }
+}
+
+#[test]
+fn dmg0_post_boot_io() {
+    check_post_boot(
+        Model::Dmg0,
+        &[
+            (0xFF04, 0x18),
+            (0xFF26, 0xF1),
+            (0xFF46, 0xFF),
+            (0xFF4D, 0xFF),
+        ],
+    );
+}
+
+#[test]
+fn dmg_post_boot_io() {
+    check_post_boot(
+        Model::Dmg,
+        &[
+            (0xFF02, 0x7E),
+            (0xFF04, 0xAB),
+            (0xFF26, 0xF1),
+            (0xFF46, 0xFF),
+            (0xFF4D, 0xFF),
+            (0xFF4F, 0xFF),
+            (0xFF55, 0xFF),
+            (0xFF70, 0xFF),
+        ],
+    );
+}
+
+#[test]
+fn mgb_post_boot_io() {
+    check_post_boot(
+        Model::Mgb,
+        &[
+            (0xFF04, 0xAB),
+            (0xFF46, 0xFF),
+            (0xFF56, 0xFF),
+            (0xFF70, 0xFF),
+        ],
+    );
+}
+
+#[test]
+fn sgb_post_boot_io() {
+    check_post_boot(
+        Model::Sgb,
+        &[
+            (0xFF04, 0xD8),
+            (0xFF26, 0xF0),
+            (0xFF46, 0xFF),
+            (0xFF4D, 0xFF),
+        ],
+    );
+}
+
+#[test]
+fn sgb2_post_boot_io() {
+    check_post_boot(
+        Model::Sgb2,
+        &[
+            (0xFF04, 0xD8),
+            (0xFF26, 0xF0),
+            (0xFF55, 0xFF),
+            (0xFF70, 0xFF),
+        ],
+    );
+}
+
+#[test]
+fn cgb_post_boot_io() {
+    check_post_boot(
+        Model::Cgb,
+        &[
+            (0xFF02, 0x7F),
+            (0xFF04, 0x1E),
+            (0xFF26, 0xF1),
+            (0xFF46, 0x00),
+            (0xFF4D, 0x7E),
+            (0xFF4F, 0xFE),
+            (0xFF55, 0xFF),
+            (0xFF56, 0x3E),
+        ],
+    );
+}
+
+#[test]
+fn agb_post_boot_io() {
+    check_post_boot(
+        Model::Agb,
+        &[
+            (0xFF02, 0x7F),
+            (0xFF46, 0x00),
+            (0xFF4D, 0x7E),
+            (0xFF4F, 0xFE),
+        ],
+    );
+}
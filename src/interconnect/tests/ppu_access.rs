@@ -0,0 +1,60 @@
+use crate::cart::Cart;
+use crate::interconnect::Interconnect;
+use crate::model::Model;
+
+fn setup_interconnect() -> Interconnect {
+    let mut inter = Interconnect::new(Cart::from_bytes(&[0; 0x8000]).unwrap(), Model::Dmg);
+    inter.write_byte(0xFF40, 0x00);
+    inter.write_byte(0x8000, 0x12);
+    inter.write_byte(0xFE00, 0x34);
+    inter.write_byte(0xFF40, 0x91);
+    inter
+}
+
+#[test]
+fn oam_blocked_in_modes_2_and_3_vram_in_mode_3() {
+    let mut inter = setup_interconnect();
+
+    // Mode 2
+    assert_eq!(inter.read_byte(0xFE00), 0xFF);
+    assert_eq!(inter.read_byte(0x8000), 0x12);
+
+    // Mode 3
+    inter.tick(80 / 4);
+    inter.write_byte(0x8000, 0x56);
+    inter.write_byte(0xFE00, 0x78);
+    assert_eq!(inter.read_byte(0xFE00), 0xFF);
+    assert_eq!(inter.read_byte(0x8000), 0xFF);
+
+    // HBlank
+    inter.tick(172 / 4);
+    assert_eq!(inter.read_byte(0xFE00), 0x34);
+    assert_eq!(inter.read_byte(0x8000), 0x12);
+}
+
+#[test]
+fn blocking_can_be_disabled() {
+    let mut inter = setup_interconnect();
+    inter.ppu.set_access_blocking(false);
+    inter.tick(80 / 4);
+
+    inter.write_byte(0x8000, 0x56);
+    assert_eq!(inter.read_byte(0x8000), 0x56);
+    assert_eq!(inter.read_byte(0xFE00), 0x34);
+}
+
+#[test]
+fn oam_dma_reads_vram_during_mode_3() {
+    let mut inter = setup_interconnect();
+    inter.write_byte(0xFF40, 0x00);
+    inter.write_byte(0x8001, 0x56);
+    inter.write_byte(0xFF40, 0x91);
+
+    // Mode 3
+    inter.tick(80 / 4);
+    inter.write_byte(0xFF46, 0x80);
+    inter.tick(1 + 2);
+
+    assert_eq!(inter.ppu.oam[0], 0x12);
+    assert_eq!(inter.ppu.oam[1], 0x56);
+}
@@ -0,0 +1,78 @@
+//0xFF0F IF  Interrupt flag (requests), upper 3 bits read as 1
+//0xFFFF IE  Interrupt enable
+//
+//Bit  Source  Vector
+// 0   VBlank  0x40
+// 1   STAT    0x48
+// 2   Timer   0x50
+// 3   Serial  0x58
+// 4   Joypad  0x60
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Interrupt {
+    VBlank = 0,
+    Stat = 1,
+    Timer = 2,
+    Serial = 3,
+    Joypad = 4,
+}
+
+impl Interrupt {
+    // Lowest bit has the highest priority
+    const PRIORITY: [Interrupt; 5] = [
+        Interrupt::VBlank,
+        Interrupt::Stat,
+        Interrupt::Timer,
+        Interrupt::Serial,
+        Interrupt::Joypad,
+    ];
+
+    pub const fn bit(self) -> u8 {
+        1 << (self as u8)
+    }
+
+    pub const fn vector(self) -> u16 {
+        0x40 + (self as u16) * 8
+    }
+}
+
+pub struct InterruptController {
+    pub enable: u8,
+    pub flag: u8,
+}
+
+impl InterruptController {
+    pub fn new() -> Self {
+        Self { enable: 0, flag: 0 }
+    }
+
+    pub fn request(&mut self, interrupt: Interrupt) {
+        self.flag |= interrupt.bit();
+    }
+
+    pub fn acknowledge(&mut self, interrupt: Interrupt) {
+        self.flag &= !interrupt.bit();
+    }
+
+    /// Enabled and requested interrupts, regardless of IME.
+    pub fn pending(&self) -> u8 {
+        self.enable & self.flag & 0x1F
+    }
+
+    /// The highest priority interrupt that is both enabled and requested.
+    pub fn highest_pending(&self) -> Option<Interrupt> {
+        let pending = self.pending();
+
+        Interrupt::PRIORITY
+            .into_iter()
+            .find(|interrupt| pending & interrupt.bit() != 0)
+    }
+
+    pub fn read_flag(&self) -> u8 {
+        0xE0 | self.flag
+    }
+
+    pub fn write_flag(&mut self, value: u8) {
+        self.flag = value & 0x1F;
+    }
+}
@@ -0,0 +1,96 @@
+//0xFF00 P1/JOYP  Joypad
+//Bit 5  Select buttons   (0 = selected)
+//Bit 4  Select d-pad     (0 = selected)
+//Bit 3  Start / Down     (0 = pressed)
+//Bit 2  Select / Up
+//Bit 1  B / Left
+//Bit 0  A / Right
+//Bits 6-7 are unused and read as 1
+
+use crate::interrupts::{Interrupt, InterruptController};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Button {
+    Right = 0,
+    Left = 1,
+    Up = 2,
+    Down = 3,
+    A = 4,
+    B = 5,
+    Select = 6,
+    Start = 7,
+}
+
+impl Button {
+    /// Bit in the mask passed to `Joypad::set_buttons`, d-pad in the low nibble.
+    pub const fn bit(self) -> u8 {
+        1 << (self as u8)
+    }
+}
+
+pub struct Joypad {
+    select: u8,
+    pressed: u8,
+}
+
+impl Joypad {
+    pub fn new() -> Self {
+        Self {
+            select: 0x00,
+            pressed: 0x00,
+        }
+    }
+
+    pub fn read_register(&self) -> u8 {
+        0xC0 | self.select | self.lines()
+    }
+
+    pub fn write_register(&mut self, value: u8, interrupts: &mut InterruptController) {
+        let before = self.lines();
+        self.select = value & 0x30;
+        self.check_interrupt(before, interrupts);
+    }
+
+    /// Replace the whole button state with `buttons`, a mask of `Button::bit` values.
+    pub fn set_buttons(&mut self, buttons: u8, interrupts: &mut InterruptController) {
+        let before = self.lines();
+        self.pressed = buttons;
+        self.check_interrupt(before, interrupts);
+    }
+
+    pub fn press(&mut self, button: Button, interrupts: &mut InterruptController) {
+        self.set_buttons(self.pressed | button.bit(), interrupts);
+    }
+
+    pub fn release(&mut self, button: Button, interrupts: &mut InterruptController) {
+        self.set_buttons(self.pressed & !button.bit(), interrupts);
+    }
+
+    /// True while a selected button holds one of the input lines low, which is what wakes STOP.
+    pub fn any_line_low(&self) -> bool {
+        self.lines() != 0x0F
+    }
+
+    // Active-low input lines P10-P13 as seen through the current selection
+    fn lines(&self) -> u8 {
+        let mut lines = 0x0F;
+
+        if self.select & 0x10 == 0 {
+            lines &= !(self.pressed & 0x0F);
+        }
+        if self.select & 0x20 == 0 {
+            lines &= !(self.pressed >> 4);
+        }
+        lines
+    }
+
+    // The interrupt fires when any input line goes from high to low
+    fn check_interrupt(&self, before: u8, interrupts: &mut InterruptController) {
+        if before & !self.lines() != 0 {
+            interrupts.request(Interrupt::Joypad);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,73 @@
+use crate::interrupts::{Interrupt, InterruptController};
+use crate::joypad::{Button, Joypad};
+
+fn setup_joypad(select: u8) -> (Joypad, InterruptController) {
+    let mut joypad = Joypad::new();
+    let mut interrupts = InterruptController::new();
+    joypad.write_register(select, &mut interrupts);
+    (joypad, interrupts)
+}
+
+#[test]
+fn nothing_selected_reads_all_high() {
+    let (mut joypad, mut interrupts) = setup_joypad(0x30);
+
+    joypad.press(Button::A, &mut interrupts);
+    joypad.press(Button::Down, &mut interrupts);
+
+    assert_eq!(joypad.read_register(), 0xFF);
+}
+
+#[test]
+fn dpad_selected_reads_active_low() {
+    let (mut joypad, mut interrupts) = setup_joypad(0x20);
+
+    joypad.press(Button::Right, &mut interrupts);
+    joypad.press(Button::Start, &mut interrupts);
+
+    assert_eq!(joypad.read_register(), 0xEE);
+}
+
+#[test]
+fn buttons_selected_reads_active_low() {
+    let (mut joypad, mut interrupts) = setup_joypad(0x10);
+
+    joypad.set_buttons(Button::B.bit() | Button::Start.bit(), &mut interrupts);
+
+    assert_eq!(joypad.read_register(), 0xD5);
+
+    joypad.release(Button::B, &mut interrupts);
+    assert_eq!(joypad.read_register(), 0xD7);
+}
+
+#[test]
+fn press_on_selected_line_requests_interrupt() {
+    let (mut joypad, mut interrupts) = setup_joypad(0x10);
+
+    joypad.press(Button::A, &mut interrupts);
+
+    assert_eq!(interrupts.flag, Interrupt::Joypad.bit());
+}
+
+#[test]
+fn press_on_unselected_line_does_not_interrupt() {
+    let (mut joypad, mut interrupts) = setup_joypad(0x20);
+
+    joypad.press(Button::A, &mut interrupts);
+    assert_eq!(interrupts.flag, 0);
+
+    // Selecting the buttons pulls the line low
+    joypad.write_register(0x10, &mut interrupts);
+    assert_eq!(interrupts.flag, Interrupt::Joypad.bit());
+}
+
+#[test]
+fn release_does_not_interrupt() {
+    let (mut joypad, mut interrupts) = setup_joypad(0x10);
+    joypad.press(Button::A, &mut interrupts);
+    interrupts.flag = 0;
+
+    joypad.release(Button::A, &mut interrupts);
+
+    assert_eq!(interrupts.flag, 0);
+}
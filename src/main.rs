@@ -1,42 +1,144 @@
 extern crate sdl2;
 
+mod apu;
 mod cart;
 mod cpu;
+mod dma;
+mod frontend;
+mod hdma;
 mod interconnect;
+mod interrupts;
+mod joypad;
+mod model;
+mod ppu;
+mod serial;
+mod timer;
 
-use std::io::Result;
+use std::env;
+use std::fs;
+use std::io::{self, Error, Result};
 
 use crate::cart::Cart;
+use crate::model::Model;
+use crate::ppu::Renderer;
+use crate::serial::device::Capture;
+use crate::serial::link::link_cable;
 
 fn main() -> Result<()> {
-    let mut cart = cart::Cart::new();
-
-    cart.filename = "/home/shanesopel/rust/FerrisBoy/roms/dmg-acid2.gb".to_string();
-    cart.cart_load()?;
-
-    let inter = interconnect::Interconnect::new(cart.rom_data);
-
-    if let Some(header) = &cart.rom_head {
-        let type2 = Cart::cart_type_name(header.type_val);
-        let lic = Cart::license_name(header.lic_code);
-        let rom_size = Cart::rom_size_bytes(header.rom_size);
-
-        println!("Cartridge Loaded");
-        println!("Title: {}", header.title);
-        println!("Cart Type: {:02X} {}", header.type_val, type2);
-        println!("Rom Size: {}", rom_size);
-        println!("Ram Size: {}", header.ram_size);
-        println!("Destination Code {:02x}", header.dest_code);
-        println!("Checksum: {:02x}", header.checksum);
-        println!("Lic Code: {:02x} {} ", header.lic_code, lic);
-        println!("Rom Version: {}", header.version);
-        println!("Global Checksum: {:04X}", header.global_checksum);
+    let mut trace = false;
+    let mut renderer = Renderer::Scanline;
+    let mut access_blocking = true;
+    let mut serial = false;
+    let mut wav_path = None;
+    let mut link_path = None;
+    let mut boot_rom_path = None;
+    let mut model = None;
+    let mut rom_path = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--trace" => trace = true,
+            "--serial" => serial = true,
+            "--fifo" => renderer = Renderer::Fifo,
+            // Let the CPU reach VRAM and OAM whatever the PPU is doing
+            "--no-access-blocking" => access_blocking = false,
+            "--wav" => wav_path = args.next(),
+            "--link" => link_path = args.next(),
+            "--boot-rom" => boot_rom_path = args.next(),
+            "--model" => {
+                let name = args.next().unwrap_or_default();
+                model = Some(name.parse().map_err(Error::other)?);
+            }
+            _ => rom_path = Some(arg),
+        }
+    }
+
+    let rom_path =
+        rom_path.unwrap_or_else(|| "/home/shanesopel/rust/FerrisBoy/roms/dmg-acid2.gb".to_string());
+
+    // "-" reads the ROM from stdin, without a save file
+    let cart = match rom_path.as_str() {
+        "-" => Cart::from_reader(io::stdin().lock()),
+        path => Cart::from_path(path),
+    }
+    .map_err(Error::other)?;
+
+    let header = &cart.rom_head;
+    let type2 = Cart::cart_type_name(header.type_val);
+    let rom_size = Cart::rom_size_bytes(header.rom_size);
+
+    println!("Cartridge Loaded");
+    println!("Title: {}", header.title);
+    println!("Entry Point: {:02X?}", header.entry);
+    println!("Cart Type: {:02X} {}", header.type_val, type2);
+    println!("Rom Size: {}", rom_size);
+    println!(
+        "Ram Size: {:02x} ({} bytes)",
+        header.ram_size, header.ram_size_bytes
+    );
+    println!("CGB Support: {:?}", header.cgb_support);
+    println!("SGB Support: {:?}", header.sgb_support);
+    println!("Destination Code {:02x}", header.dest_code);
+    println!("Checksum: {:02x}", header.checksum);
+    println!("Lic Code: {:02x} {} ", header.lic_code, header.licensee());
+    println!("Rom Version: {}", header.version);
+    println!("Global Checksum: {:04X}", header.global_checksum);
+
+    for warning in header.warnings() {
+        eprintln!("Warning: {}", warning);
+    }
+
+    // Without --model each cart runs on the hardware its header asks for
+    let model_for = |cart: &Cart| model.unwrap_or_else(|| Model::for_header(&cart.rom_head));
+
+    let cart_model = model_for(&cart);
+    let mut inter = interconnect::Interconnect::new(cart, cart_model);
+    inter.ppu.set_renderer(renderer);
+    inter.ppu.set_access_blocking(access_blocking);
+    if let Some(path) = boot_rom_path {
+        inter.load_boot_rom(fs::read(path)?)?;
     }
 
     let mut cpu = cpu::Cpu::new(inter);
+    cpu.trace = trace;
+
+    // A second Game Boy on the other end of the link cable, run without a window
+    let mut peer = match link_path {
+        Some(path) => {
+            let peer_cart = Cart::from_path(path).map_err(Error::other)?;
+
+            let (port, peer_port) = link_cable();
+            let peer_model = model_for(&peer_cart);
+            let mut peer = cpu::Cpu::new(interconnect::Interconnect::new(peer_cart, peer_model));
+            cpu.connect_serial(Box::new(port));
+            peer.connect_serial(Box::new(peer_port));
+            Some(peer)
+        }
+        None => None,
+    };
+
+    let serial_output = if serial && peer.is_none() {
+        let capture = Capture::new();
+        let output = capture.output();
+        cpu.connect_serial(Box::new(capture));
+        Some(output)
+    } else {
+        None
+    };
+
+    // Save before reporting a frontend error, so battery RAM isn't lost with it
+    let result = frontend::run(&mut cpu, peer.as_mut(), wav_path.as_deref());
+    let saved = cpu.save_ram();
+    let peer_saved = peer.as_mut().map_or(Ok(()), |peer| peer.save_ram());
+
+    result.map_err(Error::other)?;
+    saved?;
+    peer_saved?;
 
-    for _ in 0..100 {
-        cpu.step();
+    if let Some(output) = serial_output {
+        println!("Serial output:");
+        println!("{}", String::from_utf8_lossy(&output.borrow()));
     }
 
     Ok(())
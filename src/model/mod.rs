@@ -0,0 +1,70 @@
+use std::str::FromStr;
+
+use crate::cart::header::{CgbSupport, RomHeader};
+
+/// Hardware revision being emulated, which decides the state the boot ROM leaves behind.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Model {
+    /// Early original Game Boy
+    Dmg0,
+    Dmg,
+    /// Game Boy Pocket
+    Mgb,
+    Sgb,
+    Sgb2,
+    Cgb,
+    /// Game Boy Advance running Game Boy software
+    Agb,
+}
+
+impl Model {
+    /// The model to run a cartridge on when none is chosen, a CGB if the header asks for one.
+    pub fn for_header(header: &RomHeader) -> Model {
+        match header.cgb_support {
+            CgbSupport::None => Model::Dmg,
+            CgbSupport::Enhanced | CgbSupport::Only => Model::Cgb,
+        }
+    }
+
+    pub fn is_cgb(self) -> bool {
+        matches!(self, Model::Cgb | Model::Agb)
+    }
+
+    pub fn is_sgb(self) -> bool {
+        matches!(self, Model::Sgb | Model::Sgb2)
+    }
+
+    /// Internal divider value when the boot ROM hands over to the cartridge.
+    pub fn post_boot_div(self) -> u16 {
+        match self {
+            Model::Dmg0 => 0x182C,
+            Model::Dmg | Model::Mgb => 0xABCC,
+            // The SGB boot ROM's length depends on the SNES handshake, this is typical
+            Model::Sgb | Model::Sgb2 => 0xD85C,
+            Model::Cgb | Model::Agb => 0x1EA0,
+        }
+    }
+}
+
+impl FromStr for Model {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_ascii_lowercase().as_str() {
+            "dmg0" => Ok(Model::Dmg0),
+            "dmg" => Ok(Model::Dmg),
+            "mgb" => Ok(Model::Mgb),
+            "sgb" => Ok(Model::Sgb),
+            "sgb2" => Ok(Model::Sgb2),
+            "cgb" => Ok(Model::Cgb),
+            "agb" => Ok(Model::Agb),
+            _ => Err(format!(
+                "unknown model {}, expected one of dmg0, dmg, mgb, sgb, sgb2, cgb, agb",
+                name
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
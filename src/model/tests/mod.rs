@@ -0,0 +1,16 @@
+use crate::cart::Cart;
+use crate::model::Model;
+
+fn model_for(cgb_flag: u8) -> Model {
+    let mut rom = vec![0; 0x8000];
+    rom[0x0143] = cgb_flag;
+
+    Model::for_header(&Cart::from_bytes(&rom).unwrap().rom_head)
+}
+
+#[test]
+fn cgb_carts_default_to_cgb() {
+    assert_eq!(model_for(0x00), Model::Dmg);
+    assert_eq!(model_for(0x80), Model::Cgb);
+    assert_eq!(model_for(0xC0), Model::Cgb);
+}
@@ -0,0 +1,307 @@
+// Dot-by-dot mode 3: a fetcher fills the background FIFO 8 pixels at a time and one
+// pixel is shifted out per dot. Registers are read as the pixels are fetched and
+// mixed, so mid-scanline writes land where they would on hardware.
+
+use std::collections::VecDeque;
+
+use crate::ppu::{ColorMode, Ppu, OAM_SCAN_DOTS, SCANLINE_DOTS, SCREEN_WIDTH, VRAM_BANK_SIZE};
+
+// The first tile fetched on each line is thrown away
+const STARTUP_DOTS: u32 = 6;
+const SPRITE_FETCH_DOTS: u32 = 6;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum FetchStep {
+    Tile,
+    DataLow,
+    DataHigh,
+    Push,
+}
+
+#[derive(Copy, Clone)]
+struct ObjPixel {
+    color: u8,
+    attrs: u8,
+    oam_index: usize,
+}
+
+pub struct PixelFifo {
+    // Colour number and BG map attributes
+    bg: VecDeque<(u8, u8)>,
+    obj: VecDeque<ObjPixel>,
+
+    step: FetchStep,
+    // Each fetch step but Push takes two dots
+    step_dots: u8,
+    tile_x: u8,
+    tile: u8,
+    attrs: u8,
+    row: u8,
+    lo: u8,
+    hi: u8,
+    window: bool,
+
+    startup: u32,
+    // Pixels shifted out without being drawn, for SCX fine scroll or WX < 7
+    discard: u8,
+    x: usize,
+    dots: u32,
+
+    // Sprites on this line not yet fetched, by X then OAM index
+    sprites: VecDeque<usize>,
+    // OAM index and dots left of the sprite being fetched
+    sprite_fetch: Option<(usize, u32)>,
+}
+
+impl PixelFifo {
+    pub fn new(sprites: Vec<usize>, discard: u8) -> Self {
+        Self {
+            bg: VecDeque::with_capacity(16),
+            obj: VecDeque::with_capacity(8),
+            step: FetchStep::Tile,
+            step_dots: 0,
+            tile_x: 0,
+            tile: 0,
+            attrs: 0,
+            row: 0,
+            lo: 0,
+            hi: 0,
+            window: false,
+            startup: STARTUP_DOTS,
+            discard,
+            x: 0,
+            dots: 0,
+            sprites: sprites.into(),
+            sprite_fetch: None,
+        }
+    }
+}
+
+impl Ppu {
+    pub(super) fn start_pixel_transfer(&mut self) {
+        let mut sprites = self.line_sprites();
+        sprites.sort_by_key(|&i| self.oam[i * 4 + 1]);
+
+        self.fifo = PixelFifo::new(sprites, self.scx % 8);
+    }
+
+    /// Run mode 3 for the dots available, returning true once the line is complete.
+    pub(super) fn run_pixel_transfer(&mut self) -> bool {
+        while self.dots > 0 {
+            self.dots -= 1;
+            self.fifo.dots += 1;
+
+            if self.step_pixel_transfer() {
+                if self.fifo.window {
+                    self.window_line += 1;
+                }
+                self.hblank_dots = SCANLINE_DOTS - OAM_SCAN_DOTS - self.fifo.dots;
+                return true;
+            }
+        }
+        false
+    }
+
+    fn step_pixel_transfer(&mut self) -> bool {
+        if self.fifo.x >= SCREEN_WIDTH {
+            return true;
+        }
+
+        if self.fifo.startup > 0 {
+            self.fifo.startup -= 1;
+            return false;
+        }
+
+        if let Some((index, dots)) = self.fifo.sprite_fetch {
+            // The background fetch in progress finishes while the sprite is fetched
+            self.step_fetcher();
+            if dots > 1 {
+                self.fifo.sprite_fetch = Some((index, dots - 1));
+            } else {
+                self.fifo.sprite_fetch = None;
+                self.merge_sprite(index);
+            }
+            return false;
+        }
+
+        self.check_window();
+        self.step_fetcher();
+
+        // A sprite at this X stalls the FIFO, plus up to 5 dots while the background fetch
+        // for the tile in progress finishes. The FIFO holds the rest of that tile.
+        if let Some(&index) = self.fifo.sprites.front() {
+            if self.lcdc & 0x02 != 0 && self.oam[index * 4 + 1] as usize <= self.fifo.x + 8 {
+                if !self.fifo.bg.is_empty() {
+                    let wait = if self.fifo.step == FetchStep::Push {
+                        0
+                    } else {
+                        self.fifo.bg.len().saturating_sub(3) as u32
+                    };
+                    // This dot is the first of the fetch
+                    self.fifo.sprites.pop_front();
+                    self.fifo.sprite_fetch = Some((index, SPRITE_FETCH_DOTS + wait - 1));
+                }
+                return false;
+            }
+        }
+
+        self.shift_pixel();
+        self.fifo.x >= SCREEN_WIDTH
+    }
+
+    fn check_window(&mut self) {
+        if self.fifo.window
+            || self.lcdc & 0x20 == 0
+            || !self.window_y_triggered
+            || (self.fifo.x as u16 + 7) < self.wx as u16
+        {
+            return;
+        }
+
+        self.fifo.window = true;
+        self.fifo.bg.clear();
+        self.fifo.tile_x = 0;
+        self.fifo.step = FetchStep::Tile;
+        self.fifo.step_dots = 0;
+        if self.wx < 7 {
+            self.fifo.discard = 7 - self.wx;
+        }
+    }
+
+    fn step_fetcher(&mut self) {
+        if self.fifo.step == FetchStep::Push {
+            // Only pushes into an empty FIFO
+            if self.fifo.bg.is_empty() {
+                let xflip = self.fifo.attrs & 0x20 != 0;
+                for col in 0..8 {
+                    let bit = if xflip { col } else { 7 - col };
+                    let color = Self::tile_pixel(self.fifo.lo, self.fifo.hi, bit);
+                    self.fifo.bg.push_back((color, self.fifo.attrs));
+                }
+
+                self.fifo.tile_x = self.fifo.tile_x.wrapping_add(1);
+                self.fifo.step = FetchStep::Tile;
+            }
+            return;
+        }
+
+        self.fifo.step_dots += 1;
+        if self.fifo.step_dots < 2 {
+            return;
+        }
+        self.fifo.step_dots = 0;
+
+        match self.fifo.step {
+            FetchStep::Tile => {
+                self.fetch_tile();
+                self.fifo.step = FetchStep::DataLow;
+            }
+            FetchStep::DataLow => {
+                self.fifo.lo = self.vram[self.tile_row_address()];
+                self.fifo.step = FetchStep::DataHigh;
+            }
+            FetchStep::DataHigh => {
+                self.fifo.hi = self.vram[self.tile_row_address() + 1];
+                self.fifo.step = FetchStep::Push;
+            }
+            FetchStep::Push => (),
+        }
+    }
+
+    fn fetch_tile(&mut self) {
+        let (map_select, map_x, y) = if self.fifo.window {
+            (0x40, self.fifo.tile_x, self.window_line)
+        } else {
+            (
+                0x08,
+                (self.scx / 8).wrapping_add(self.fifo.tile_x),
+                self.ly.wrapping_add(self.scy),
+            )
+        };
+        let map_base = if self.lcdc & map_select != 0 {
+            0x1C00
+        } else {
+            0x1800
+        };
+
+        let map_index = map_base + (y as usize / 8) * 32 + (map_x as usize & 0x1F);
+        self.fifo.tile = self.vram[map_index];
+        self.fifo.attrs = if self.color_mode == ColorMode::Cgb {
+            self.vram[VRAM_BANK_SIZE + map_index]
+        } else {
+            0
+        };
+        self.fifo.row = if self.fifo.attrs & 0x40 != 0 {
+            7 - y % 8
+        } else {
+            y % 8
+        };
+    }
+
+    fn tile_row_address(&self) -> usize {
+        let bank = if self.fifo.attrs & 0x08 != 0 {
+            VRAM_BANK_SIZE
+        } else {
+            0
+        };
+        bank + self.tile_data_address(self.fifo.tile) + self.fifo.row as usize * 2
+    }
+
+    fn merge_sprite(&mut self, index: usize) {
+        let pixels = self.sprite_pixels(index);
+        let attrs = self.oam[index * 4 + 3];
+        let cgb = self.color_mode == ColorMode::Cgb;
+
+        // Only sprites with X < 8 start left of the FIFO position
+        let skip = (self.fifo.x + 8).saturating_sub(self.oam[index * 4 + 1] as usize);
+
+        for (slot, &color) in pixels.iter().skip(skip).enumerate() {
+            let pixel = ObjPixel {
+                color,
+                attrs,
+                oam_index: index,
+            };
+
+            match self.fifo.obj.get_mut(slot) {
+                // Sprites fetched earlier have the smaller X and win on DMG, CGB goes by OAM index
+                Some(old) => {
+                    if old.color == 0 || (cgb && color != 0 && index < old.oam_index) {
+                        *old = pixel;
+                    }
+                }
+                None => self.fifo.obj.push_back(pixel),
+            }
+        }
+    }
+
+    fn shift_pixel(&mut self) {
+        let Some((color, bg_attrs)) = self.fifo.bg.pop_front() else {
+            return;
+        };
+
+        if self.fifo.discard > 0 {
+            self.fifo.discard -= 1;
+            return;
+        }
+
+        // Outside CGB mode LCDC bit 0 blanks the BG and window
+        let bg_color = if self.color_mode != ColorMode::Cgb && self.lcdc & 0x01 == 0 {
+            0
+        } else {
+            color
+        };
+        let mut shade = self.bg_shade(bg_attrs, bg_color);
+
+        if let Some(obj) = self.fifo.obj.pop_front() {
+            if obj.color != 0
+                && self.lcdc & 0x02 != 0
+                && self.sprite_visible(obj.attrs, bg_color, bg_attrs)
+            {
+                shade = self.obj_shade(obj.attrs, obj.color);
+            }
+        }
+
+        self.framebuffer[self.ly as usize * SCREEN_WIDTH + self.fifo.x] = shade;
+        self.fifo.x += 1;
+    }
+}
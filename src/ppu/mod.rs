@@ -0,0 +1,649 @@
+//0xFF40 LCDC  LCD control
+//0xFF41 STAT  LCD status (bits 3-6 writable, bits 0-2 read only). The enabled mode 0/1/2
+//              and LY=LYC sources are OR'd into one line, the interrupt fires on its rising edge
+//0xFF42 SCY   Background viewport Y
+//0xFF43 SCX   Background viewport X
+//0xFF44 LY    Current scanline (read only)
+//0xFF45 LYC   Scanline compare
+//0xFF47 BGP   Background palette
+//0xFF48 OBP0  Object palette 0
+//0xFF49 OBP1  Object palette 1
+//0xFF4A WY    Window Y position
+//0xFF4B WX    Window X position + 7
+//0xFF4F VBK   VRAM bank (CGB mode only)
+//0xFF68 BCPS  Background palette index, bit 7 auto-increments (CGB only)
+//0xFF69 BCPD  Background palette data (CGB only)
+//0xFF6A OCPS  Object palette index, bit 7 auto-increments (CGB only)
+//0xFF6B OCPD  Object palette data (CGB only)
+
+mod fifo;
+
+use crate::interrupts::{Interrupt, InterruptController};
+use fifo::PixelFifo;
+
+pub const SCREEN_WIDTH: usize = 160;
+pub const SCREEN_HEIGHT: usize = 144;
+
+const OAM_SCAN_DOTS: u32 = 80;
+// Shortest mode 3, the FIFO renderer stretches it for scrolling, the window and sprites
+const PIXEL_TRANSFER_DOTS: u32 = 172;
+const HBLANK_DOTS: u32 = 204;
+const SCANLINE_DOTS: u32 = 456;
+
+const VBLANK_LINE: u8 = 144;
+const LINES_PER_FRAME: u8 = 154;
+
+const SPRITES_PER_LINE: usize = 10;
+
+// ARGB8888 shades for DMG colour numbers 0-3
+const SHADES: [u32; 4] = [0xFFFFFFFF, 0xFFAAAAAA, 0xFF555555, 0xFF000000];
+
+// RGB555 greys loaded for DMG carts on a CGB, the boot ROM would pick a palette by title
+const COMPAT_PALETTE: [u16; 4] = [0x7FFF, 0x56B5, 0x294A, 0x0000];
+
+const VRAM_BANK_SIZE: usize = 0x2000;
+const PALETTE_RAM_SIZE: usize = 0x40;
+
+/// How the PPU turns colour numbers into pixels.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Original Game Boy, four shades through BGP/OBP0/OBP1
+    Dmg,
+    /// CGB cartridge on a CGB, colour palettes and BG map attributes
+    Cgb,
+    /// Old cartridge on a CGB, DMG palettes index into CGB palette RAM
+    DmgCompat,
+}
+
+/// How mode 3 is emulated.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Renderer {
+    /// Draws the whole line at the end of a fixed length mode 3
+    Scanline,
+    /// Draws a pixel per dot, for raster effects and variable mode 3 length
+    Fifo,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PpuMode {
+    HBlank = 0,
+    VBlank = 1,
+    OamScan = 2,
+    PixelTransfer = 3,
+}
+
+pub struct Ppu {
+    // Bank 1 holds the CGB tile data and BG map attributes
+    pub vram: [u8; VRAM_BANK_SIZE * 2],
+    pub oam: [u8; 0xA0],
+
+    color_mode: ColorMode,
+    vram_bank: usize,
+    bcps: u8,
+    ocps: u8,
+    bg_palettes: [u8; PALETTE_RAM_SIZE],
+    obj_palettes: [u8; PALETTE_RAM_SIZE],
+
+    lcdc: u8,
+    stat: u8,
+    scy: u8,
+    scx: u8,
+    ly: u8,
+    lyc: u8,
+    bgp: u8,
+    obp0: u8,
+    obp1: u8,
+    wy: u8,
+    wx: u8,
+
+    mode: PpuMode,
+    dots: u32,
+    stat_line: bool,
+
+    // Internal window line counter, only advances on lines the window was drawn.
+    window_line: u8,
+    // LY matched WY at some point this frame
+    window_y_triggered: bool,
+
+    renderer: Renderer,
+    // Renderer for the line in progress, latched at the end of OAM scan
+    line_renderer: Renderer,
+    fifo: PixelFifo,
+    // The CPU can't reach VRAM in mode 3 or OAM in modes 2-3, can be turned off for debugging
+    access_blocking: bool,
+    // Length of the current HBlank, what is left of the line after mode 3
+    hblank_dots: u32,
+
+    framebuffer: Vec<u32>,
+    frame_ready: bool,
+
+    // Entries into HBlank not yet seen by HBlank DMA, which copies a block for each
+    hblank_starts: u32,
+}
+
+impl Ppu {
+    pub fn new(color_mode: ColorMode) -> Self {
+        let mut ppu = Self {
+            vram: [0; VRAM_BANK_SIZE * 2],
+            oam: [0; 0xA0],
+            color_mode,
+            vram_bank: 0,
+            bcps: 0,
+            ocps: 0,
+            bg_palettes: [0xFF; PALETTE_RAM_SIZE],
+            obj_palettes: [0xFF; PALETTE_RAM_SIZE],
+            lcdc: 0,
+            stat: 0,
+            scy: 0,
+            scx: 0,
+            ly: 0,
+            lyc: 0,
+            bgp: 0,
+            obp0: 0,
+            obp1: 0,
+            wy: 0,
+            wx: 0,
+            mode: PpuMode::HBlank,
+            dots: 0,
+            stat_line: false,
+            window_line: 0,
+            window_y_triggered: false,
+            renderer: Renderer::Scanline,
+            line_renderer: Renderer::Scanline,
+            access_blocking: true,
+            fifo: PixelFifo::new(Vec::new(), 0),
+            hblank_dots: HBLANK_DOTS,
+            framebuffer: vec![SHADES[0]; SCREEN_WIDTH * SCREEN_HEIGHT],
+            frame_ready: false,
+            hblank_starts: 0,
+        };
+
+        if color_mode == ColorMode::DmgCompat {
+            let palette: Vec<u8> = COMPAT_PALETTE
+                .iter()
+                .flat_map(|c| c.to_le_bytes())
+                .collect();
+
+            // BG palette 0, OBJ palettes 0 and 1
+            ppu.bg_palettes[..8].copy_from_slice(&palette);
+            ppu.obj_palettes[..8].copy_from_slice(&palette);
+            ppu.obj_palettes[8..16].copy_from_slice(&palette);
+        }
+
+        ppu
+    }
+
+    /// Switch renderers, takes effect from the next line.
+    pub fn set_renderer(&mut self, renderer: Renderer) {
+        self.renderer = renderer;
+    }
+
+    pub fn set_access_blocking(&mut self, enabled: bool) {
+        self.access_blocking = enabled;
+    }
+
+    pub fn vram_accessible(&self) -> bool {
+        !self.access_blocking || !self.lcd_enabled() || self.mode != PpuMode::PixelTransfer
+    }
+
+    pub fn oam_accessible(&self) -> bool {
+        !self.access_blocking
+            || !self.lcd_enabled()
+            || !matches!(self.mode, PpuMode::OamScan | PpuMode::PixelTransfer)
+    }
+
+    pub fn color_mode(&self) -> ColorMode {
+        self.color_mode
+    }
+
+    pub fn read_vram(&self, address: u16) -> u8 {
+        self.vram[self.vram_bank * VRAM_BANK_SIZE + (address - 0x8000) as usize]
+    }
+
+    pub fn write_vram(&mut self, address: u16, value: u8) {
+        self.vram[self.vram_bank * VRAM_BANK_SIZE + (address - 0x8000) as usize] = value;
+    }
+
+    pub fn framebuffer(&self) -> &[u32] {
+        &self.framebuffer
+    }
+
+    /// Returns true once per completed frame (on entering VBlank).
+    pub fn take_frame_ready(&mut self) -> bool {
+        std::mem::replace(&mut self.frame_ready, false)
+    }
+
+    /// Number of transitions into HBlank since the last call.
+    pub fn take_hblank_starts(&mut self) -> u32 {
+        std::mem::take(&mut self.hblank_starts)
+    }
+
+    pub fn read_register(&self, address: u16) -> u8 {
+        match address {
+            0xFF40 => self.lcdc,
+            0xFF41 => {
+                let mode = if self.lcd_enabled() {
+                    self.mode as u8
+                } else {
+                    0
+                };
+                0x80 | (self.stat & 0x78) | ((self.ly == self.lyc) as u8) << 2 | mode
+            }
+            0xFF42 => self.scy,
+            0xFF43 => self.scx,
+            0xFF44 => self.ly,
+            0xFF45 => self.lyc,
+            0xFF47 => self.bgp,
+            0xFF48 => self.obp0,
+            0xFF49 => self.obp1,
+            0xFF4A => self.wy,
+            0xFF4B => self.wx,
+            0xFF4F if self.color_mode == ColorMode::Cgb => 0xFE | self.vram_bank as u8,
+            0xFF68 if self.color_mode == ColorMode::Cgb => 0x40 | self.bcps,
+            0xFF69 if self.color_mode == ColorMode::Cgb => {
+                self.bg_palettes[(self.bcps & 0x3F) as usize]
+            }
+            0xFF6A if self.color_mode == ColorMode::Cgb => 0x40 | self.ocps,
+            0xFF6B if self.color_mode == ColorMode::Cgb => {
+                self.obj_palettes[(self.ocps & 0x3F) as usize]
+            }
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write_register(
+        &mut self,
+        address: u16,
+        value: u8,
+        interrupts: &mut InterruptController,
+    ) {
+        match address {
+            0xFF40 => {
+                let was_enabled = self.lcd_enabled();
+                self.lcdc = value;
+
+                if was_enabled && !self.lcd_enabled() {
+                    self.ly = 0;
+                    self.dots = 0;
+                    self.mode = PpuMode::HBlank;
+                } else if !was_enabled && self.lcd_enabled() {
+                    self.ly = 0;
+                    self.dots = 0;
+                    self.window_line = 0;
+                    self.window_y_triggered = false;
+                    self.mode = PpuMode::OamScan;
+                }
+            }
+            0xFF41 => {
+                // DMG bug: for a cycle the write acts as if modes 0, 1 and LY=LYC were all enabled
+                if self.color_mode == ColorMode::Dmg {
+                    self.stat = 0x58;
+                    self.update_stat_line(interrupts);
+                }
+                self.stat = value & 0x78;
+            }
+            0xFF42 => self.scy = value,
+            0xFF43 => self.scx = value,
+            0xFF44 => {} // LY is read only
+            0xFF45 => self.lyc = value,
+            0xFF47 => self.bgp = value,
+            0xFF48 => self.obp0 = value,
+            0xFF49 => self.obp1 = value,
+            0xFF4A => self.wy = value,
+            0xFF4B => self.wx = value,
+            _ if self.color_mode != ColorMode::Cgb => (),
+            0xFF4F => self.vram_bank = (value & 0x01) as usize,
+            0xFF68 => self.bcps = value & 0xBF,
+            0xFF69 => Self::write_palette(&mut self.bg_palettes, &mut self.bcps, value),
+            0xFF6A => self.ocps = value & 0xBF,
+            0xFF6B => Self::write_palette(&mut self.obj_palettes, &mut self.ocps, value),
+            _ => (),
+        }
+
+        if matches!(address, 0xFF40 | 0xFF41 | 0xFF45) {
+            self.update_stat_line(interrupts);
+        }
+    }
+
+    fn write_palette(palettes: &mut [u8; PALETTE_RAM_SIZE], index: &mut u8, value: u8) {
+        palettes[(*index & 0x3F) as usize] = value;
+
+        if *index & 0x80 != 0 {
+            *index = 0x80 | (index.wrapping_add(1) & 0x3F);
+        }
+    }
+
+    /// Advance the PPU by `cycles` dots (T-cycles).
+    pub fn tick(&mut self, cycles: u32, interrupts: &mut InterruptController) {
+        if !self.lcd_enabled() {
+            return;
+        }
+
+        self.dots += cycles;
+
+        loop {
+            match self.mode {
+                PpuMode::OamScan => {
+                    if self.dots < OAM_SCAN_DOTS {
+                        break;
+                    }
+                    self.dots -= OAM_SCAN_DOTS;
+
+                    if self.ly == self.wy {
+                        self.window_y_triggered = true;
+                    }
+                    self.line_renderer = self.renderer;
+                    if self.line_renderer == Renderer::Fifo {
+                        self.start_pixel_transfer();
+                    }
+                    self.set_mode(PpuMode::PixelTransfer);
+                }
+                PpuMode::PixelTransfer => {
+                    match self.line_renderer {
+                        Renderer::Scanline => {
+                            if self.dots < PIXEL_TRANSFER_DOTS {
+                                break;
+                            }
+                            self.dots -= PIXEL_TRANSFER_DOTS;
+                            self.render_scanline();
+                            self.hblank_dots = HBLANK_DOTS;
+                        }
+                        Renderer::Fifo => {
+                            if !self.run_pixel_transfer() {
+                                break;
+                            }
+                        }
+                    }
+                    self.hblank_starts += 1;
+                    self.set_mode(PpuMode::HBlank);
+                }
+                PpuMode::HBlank => {
+                    if self.dots < self.hblank_dots {
+                        break;
+                    }
+                    self.dots -= self.hblank_dots;
+                    self.ly += 1;
+
+                    if self.ly == VBLANK_LINE {
+                        self.frame_ready = true;
+                        interrupts.request(Interrupt::VBlank);
+                        self.set_mode(PpuMode::VBlank);
+                    } else {
+                        self.set_mode(PpuMode::OamScan);
+                    }
+                }
+                PpuMode::VBlank => {
+                    if self.dots < SCANLINE_DOTS {
+                        break;
+                    }
+                    self.dots -= SCANLINE_DOTS;
+                    self.ly += 1;
+
+                    if self.ly == LINES_PER_FRAME {
+                        self.ly = 0;
+                        self.window_line = 0;
+                        self.window_y_triggered = false;
+                        self.set_mode(PpuMode::OamScan);
+                    }
+                }
+            }
+
+            self.update_stat_line(interrupts);
+        }
+    }
+
+    pub fn lcd_enabled(&self) -> bool {
+        self.lcdc & 0x80 != 0
+    }
+
+    fn set_mode(&mut self, mode: PpuMode) {
+        self.mode = mode;
+    }
+
+    // Request the STAT interrupt on a rising edge of the combined STAT sources
+    fn update_stat_line(&mut self, interrupts: &mut InterruptController) {
+        let source = match self.mode {
+            PpuMode::HBlank => 0x08,
+            PpuMode::VBlank => 0x10,
+            PpuMode::OamScan => 0x20,
+            PpuMode::PixelTransfer => 0x00,
+        };
+        let coincidence = self.ly == self.lyc && self.stat & 0x40 != 0;
+
+        let line = self.lcd_enabled() && (self.stat & source != 0 || coincidence);
+        if line && !self.stat_line {
+            interrupts.request(Interrupt::Stat);
+        }
+        self.stat_line = line;
+    }
+
+    fn render_scanline(&mut self) {
+        // Colour numbers (before palette) and map attributes of the BG/window,
+        // needed for sprite priority. Attributes are always 0 outside CGB mode.
+        let mut bg_pixels = [(0u8, 0u8); SCREEN_WIDTH];
+
+        if self.lcdc & 0x01 != 0 || self.color_mode == ColorMode::Cgb {
+            self.render_background(&mut bg_pixels);
+            self.render_window(&mut bg_pixels);
+        }
+
+        let line_start = self.ly as usize * SCREEN_WIDTH;
+        for (x, &(color, attrs)) in bg_pixels.iter().enumerate() {
+            self.framebuffer[line_start + x] = self.bg_shade(attrs, color);
+        }
+
+        if self.lcdc & 0x02 != 0 {
+            self.render_sprites(&bg_pixels);
+        }
+    }
+
+    fn render_background(&self, bg_pixels: &mut [(u8, u8); SCREEN_WIDTH]) {
+        let map_base = if self.lcdc & 0x08 != 0 {
+            0x1C00
+        } else {
+            0x1800
+        };
+        let y = self.ly.wrapping_add(self.scy);
+
+        for (x, pixel) in bg_pixels.iter_mut().enumerate() {
+            let px = (x as u8).wrapping_add(self.scx);
+            *pixel = self.tile_map_pixel(map_base, px, y);
+        }
+    }
+
+    fn render_window(&mut self, bg_pixels: &mut [(u8, u8); SCREEN_WIDTH]) {
+        if self.lcdc & 0x20 == 0 || self.ly < self.wy || self.wx > 166 {
+            return;
+        }
+
+        let map_base = if self.lcdc & 0x40 != 0 {
+            0x1C00
+        } else {
+            0x1800
+        };
+        let start = self.wx as i16 - 7;
+        let mut drawn = false;
+
+        for (x, pixel) in bg_pixels.iter_mut().enumerate() {
+            let wx = x as i16 - start;
+            if wx < 0 {
+                continue;
+            }
+            *pixel = self.tile_map_pixel(map_base, wx as u8, self.window_line);
+            drawn = true;
+        }
+
+        if drawn {
+            self.window_line += 1;
+        }
+    }
+
+    fn render_sprites(&mut self, bg_pixels: &[(u8, u8); SCREEN_WIDTH]) {
+        let mut sprites = self.line_sprites();
+
+        // On DMG the sprite with the smaller X wins, ties go to the lower OAM index.
+        // In CGB mode only the OAM index counts.
+        if self.color_mode != ColorMode::Cgb {
+            sprites.sort_by_key(|&i| self.oam[i * 4 + 1]);
+        }
+
+        let rows: Vec<(usize, [u8; 8])> = sprites
+            .iter()
+            .map(|&i| (i, self.sprite_pixels(i)))
+            .collect();
+        let line_start = self.ly as usize * SCREEN_WIDTH;
+
+        for (x, &(bg_color, bg_attrs)) in bg_pixels.iter().enumerate() {
+            for &(i, pixels) in &rows {
+                let sprite_x = self.oam[i * 4 + 1] as i16 - 8;
+                let col = x as i16 - sprite_x;
+                if !(0..8).contains(&col) || pixels[col as usize] == 0 {
+                    continue;
+                }
+
+                let attrs = self.oam[i * 4 + 3];
+                if self.sprite_visible(attrs, bg_color, bg_attrs) {
+                    self.framebuffer[line_start + x] = self.obj_shade(attrs, pixels[col as usize]);
+                }
+                break;
+            }
+        }
+    }
+
+    // OAM scan: first 10 sprites overlapping this line, in OAM order
+    fn line_sprites(&self) -> Vec<usize> {
+        let height: i16 = if self.lcdc & 0x04 != 0 { 16 } else { 8 };
+        let ly = self.ly as i16;
+
+        (0..40)
+            .filter(|&i| {
+                let y = self.oam[i * 4] as i16 - 16;
+                ly >= y && ly < y + height
+            })
+            .take(SPRITES_PER_LINE)
+            .collect()
+    }
+
+    // Colour numbers of a sprite's row on the current line, left to right
+    fn sprite_pixels(&self, i: usize) -> [u8; 8] {
+        let height: i16 = if self.lcdc & 0x04 != 0 { 16 } else { 8 };
+        let sprite_y = self.oam[i * 4] as i16 - 16;
+        let mut tile = self.oam[i * 4 + 2];
+        let attrs = self.oam[i * 4 + 3];
+
+        let mut row = self.ly as i16 - sprite_y;
+        if attrs & 0x40 != 0 {
+            row = height - 1 - row;
+        }
+        if height == 16 {
+            tile &= 0xFE;
+        }
+
+        let bank = if self.color_mode == ColorMode::Cgb && attrs & 0x08 != 0 {
+            VRAM_BANK_SIZE
+        } else {
+            0
+        };
+        let addr = bank + tile as usize * 16 + row as usize * 2;
+
+        let mut pixels = [0; 8];
+        for (col, pixel) in pixels.iter_mut().enumerate() {
+            let bit = if attrs & 0x20 != 0 { col } else { 7 - col };
+            *pixel = Self::tile_pixel(self.vram[addr], self.vram[addr + 1], bit as u8);
+        }
+        pixels
+    }
+
+    // Whether an opaque sprite pixel shows over the BG/window pixel beneath it
+    fn sprite_visible(&self, attrs: u8, bg_color: u8, bg_attrs: u8) -> bool {
+        // In CGB mode LCDC bit 0 clear puts sprites above the BG regardless of priority
+        let bg_master_priority = self.color_mode != ColorMode::Cgb || self.lcdc & 0x01 != 0;
+        let behind_bg = (attrs | bg_attrs) & 0x80 != 0;
+
+        !(bg_master_priority && behind_bg && bg_color != 0)
+    }
+
+    // Returns the colour number and, in CGB mode, the BG map attributes:
+    // bits 0-2 palette, bit 3 VRAM bank, bit 5 X flip, bit 6 Y flip, bit 7 priority
+    fn tile_map_pixel(&self, map_base: usize, x: u8, y: u8) -> (u8, u8) {
+        let map_index = map_base + (y as usize / 8) * 32 + (x as usize / 8);
+        let tile = self.vram[map_index];
+        let attrs = if self.color_mode == ColorMode::Cgb {
+            self.vram[VRAM_BANK_SIZE + map_index]
+        } else {
+            0
+        };
+
+        let row = if attrs & 0x40 != 0 { 7 - y % 8 } else { y % 8 };
+        let col = x % 8;
+        let bit = if attrs & 0x20 != 0 { col } else { 7 - col };
+        let bank = if attrs & 0x08 != 0 { VRAM_BANK_SIZE } else { 0 };
+
+        let addr = bank + self.tile_data_address(tile) + row as usize * 2;
+        (
+            Self::tile_pixel(self.vram[addr], self.vram[addr + 1], bit),
+            attrs,
+        )
+    }
+
+    fn bg_shade(&self, attrs: u8, color: u8) -> u32 {
+        match self.color_mode {
+            ColorMode::Dmg => SHADES[Self::apply_palette(self.bgp, color)],
+            ColorMode::Cgb => Self::palette_color(&self.bg_palettes, attrs & 0x07, color),
+            ColorMode::DmgCompat => {
+                let shade = Self::apply_palette(self.bgp, color) as u8;
+                Self::palette_color(&self.bg_palettes, 0, shade)
+            }
+        }
+    }
+
+    fn obj_shade(&self, attrs: u8, color: u8) -> u32 {
+        // DMG sprites pick OBP0/OBP1 with bit 4, CGB sprites a palette with bits 0-2
+        let (dmg_palette, palette) = if attrs & 0x10 != 0 {
+            (1, self.obp1)
+        } else {
+            (0, self.obp0)
+        };
+
+        match self.color_mode {
+            ColorMode::Dmg => SHADES[Self::apply_palette(palette, color)],
+            ColorMode::Cgb => Self::palette_color(&self.obj_palettes, attrs & 0x07, color),
+            ColorMode::DmgCompat => {
+                let shade = Self::apply_palette(palette, color) as u8;
+                Self::palette_color(&self.obj_palettes, dmg_palette, shade)
+            }
+        }
+    }
+
+    // Look up an RGB555 colour in palette RAM and expand it to ARGB8888
+    fn palette_color(palettes: &[u8; PALETTE_RAM_SIZE], palette: u8, color: u8) -> u32 {
+        let index = palette as usize * 8 + color as usize * 2;
+        let rgb = u16::from_le_bytes([palettes[index], palettes[index + 1]]) as u32;
+
+        let expand = |channel: u32| (channel << 3) | (channel >> 2);
+        let red = expand(rgb & 0x1F);
+        let green = expand((rgb >> 5) & 0x1F);
+        let blue = expand((rgb >> 10) & 0x1F);
+
+        0xFF000000 | red << 16 | green << 8 | blue
+    }
+
+    fn tile_data_address(&self, tile: u8) -> usize {
+        if self.lcdc & 0x10 != 0 {
+            tile as usize * 16
+        } else {
+            (0x1000 + (tile as i8 as i32) * 16) as usize
+        }
+    }
+
+    fn tile_pixel(lo: u8, hi: u8, bit: u8) -> u8 {
+        (((hi >> bit) & 1) << 1) | ((lo >> bit) & 1)
+    }
+
+    fn apply_palette(palette: u8, color: u8) -> usize {
+        ((palette >> (color * 2)) & 0x03) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,78 @@
+use crate::interrupts::InterruptController;
+use crate::ppu::{ColorMode, Ppu};
+
+const WHITE: u32 = 0xFFFFFFFF;
+const RED: u32 = 0xFFFF0000;
+const BLUE: u32 = 0xFF0000FF;
+
+fn setup_ppu() -> Ppu {
+    let mut ppu = Ppu::new(ColorMode::Cgb);
+
+    // BG palette 2 colour 3 red, OBJ palette 5 colour 3 blue
+    ppu.write_register(0xFF68, 0x80 | (2 * 8 + 6), &mut InterruptController::new());
+    ppu.write_register(0xFF69, 0x1F, &mut InterruptController::new());
+    ppu.write_register(0xFF69, 0x00, &mut InterruptController::new());
+    ppu.write_register(0xFF6A, 0x80 | (5 * 8 + 6), &mut InterruptController::new());
+    ppu.write_register(0xFF6B, 0x00, &mut InterruptController::new());
+    ppu.write_register(0xFF6B, 0x7C, &mut InterruptController::new());
+
+    // Tile 1 in bank 1: left half colour 3
+    for byte in ppu.vram[0x2010..0x2020].iter_mut() {
+        *byte = 0xF0;
+    }
+    ppu
+}
+
+fn render_line(ppu: &mut Ppu) {
+    ppu.write_register(0xFF40, ppu.lcdc | 0x80, &mut InterruptController::new());
+    ppu.tick(80 + 172, &mut InterruptController::new());
+}
+
+#[test]
+fn palette_index_auto_increments() {
+    let mut ppu = setup_ppu();
+
+    assert_eq!(ppu.read_register(0xFF68), 0xC0 | (2 * 8 + 8));
+
+    ppu.write_register(0xFF68, 2 * 8 + 6, &mut InterruptController::new());
+    assert_eq!(ppu.read_register(0xFF69), 0x1F);
+    ppu.write_register(0xFF69, 0x1F, &mut InterruptController::new());
+    assert_eq!(ppu.read_register(0xFF68), 0x40 | (2 * 8 + 6));
+}
+
+#[test]
+fn background_attributes_select_bank_palette_and_flip() {
+    let mut ppu = setup_ppu();
+    ppu.lcdc = 0x11;
+    ppu.vram[0x1800] = 1;
+    // Palette 2, VRAM bank 1, X flip
+    ppu.vram[0x3800] = 0x2A;
+
+    render_line(&mut ppu);
+
+    assert_eq!(ppu.framebuffer()[3], WHITE);
+    assert_eq!(ppu.framebuffer()[4], RED);
+    assert_eq!(ppu.framebuffer()[7], RED);
+}
+
+#[test]
+fn bg_priority_attribute_hides_sprites() {
+    let mut ppu = setup_ppu();
+    ppu.lcdc = 0x13;
+    ppu.vram[0x1800] = 1;
+    ppu.vram[0x3800] = 0x8A;
+    // Palette 5, VRAM bank 1
+    ppu.oam[0..4].copy_from_slice(&[16, 8, 1, 0x0D]);
+    ppu.oam[4..8].copy_from_slice(&[16, 8 + 8, 1, 0x0D]);
+
+    render_line(&mut ppu);
+
+    assert_eq!(ppu.framebuffer()[0], RED);
+    assert_eq!(ppu.framebuffer()[8], BLUE);
+
+    // LCDC bit 0 clear takes priority away from the background
+    ppu.lcdc = 0x12;
+    render_line(&mut ppu);
+
+    assert_eq!(ppu.framebuffer()[0], BLUE);
+}
@@ -0,0 +1,127 @@
+use crate::interrupts::InterruptController;
+use crate::ppu::{ColorMode, Ppu, PpuMode, Renderer, SCREEN_WIDTH};
+
+const WHITE: u32 = 0xFFFFFFFF;
+const BLACK: u32 = 0xFF000000;
+
+fn setup_ppu(renderer: Renderer) -> Ppu {
+    let mut ppu = Ppu::new(ColorMode::Dmg);
+    ppu.set_renderer(renderer);
+    ppu.write_register(0xFF47, 0xE4, &mut InterruptController::new());
+    ppu.write_register(0xFF48, 0xE4, &mut InterruptController::new());
+
+    // Tile 1: left half colour 3
+    for byte in ppu.vram[16..32].iter_mut() {
+        *byte = 0xF0;
+    }
+    for i in 0..32 {
+        ppu.vram[0x1800 + i] = (i % 2) as u8;
+    }
+    ppu.lcdc = 0x13;
+    ppu
+}
+
+// Start line 0 and return the length of its mode 3
+fn mode3_length(ppu: &mut Ppu) -> u32 {
+    let mut interrupts = InterruptController::new();
+    ppu.write_register(0xFF40, ppu.lcdc | 0x80, &mut InterruptController::new());
+    ppu.tick(80, &mut interrupts);
+
+    let mut dots = 0;
+    while ppu.mode == PpuMode::PixelTransfer {
+        ppu.tick(1, &mut interrupts);
+        dots += 1;
+    }
+    dots
+}
+
+#[test]
+fn mode3_is_172_dots_without_scroll_or_sprites() {
+    let mut ppu = setup_ppu(Renderer::Fifo);
+
+    assert_eq!(mode3_length(&mut ppu), 172);
+}
+
+#[test]
+fn fine_scroll_window_and_sprites_lengthen_mode3() {
+    let mut ppu = setup_ppu(Renderer::Fifo);
+    ppu.scx = 3;
+    assert_eq!(mode3_length(&mut ppu), 175);
+
+    let mut ppu = setup_ppu(Renderer::Fifo);
+    ppu.lcdc |= 0x20;
+    ppu.wx = 7 + 80;
+    assert_eq!(mode3_length(&mut ppu), 178);
+
+    let mut ppu = setup_ppu(Renderer::Fifo);
+    ppu.oam[0..4].copy_from_slice(&[16, 8 + 20, 1, 0]);
+    assert_eq!(mode3_length(&mut ppu), 179);
+}
+
+#[test]
+fn matches_scanline_renderer() {
+    let mut scanline = setup_ppu(Renderer::Scanline);
+    let mut fifo = setup_ppu(Renderer::Fifo);
+    for ppu in [&mut scanline, &mut fifo] {
+        ppu.scx = 5;
+        ppu.oam[0..4].copy_from_slice(&[16, 8 + 20, 1, 0x20]);
+        ppu.oam[4..8].copy_from_slice(&[16, 4, 1, 0]);
+        mode3_length(ppu);
+    }
+
+    assert_eq!(
+        fifo.framebuffer()[..SCREEN_WIDTH],
+        scanline.framebuffer()[..SCREEN_WIDTH]
+    );
+}
+
+#[test]
+fn palette_write_mid_line_takes_effect() {
+    let mut ppu = setup_ppu(Renderer::Fifo);
+    ppu.lcdc = 0x11;
+    for i in 0..32 {
+        ppu.vram[0x1800 + i] = 1;
+    }
+
+    let mut interrupts = InterruptController::new();
+    ppu.write_register(0xFF40, 0x91, &mut InterruptController::new());
+    ppu.tick(80 + 12 + 80, &mut interrupts);
+    ppu.write_register(0xFF47, 0x00, &mut InterruptController::new());
+    ppu.tick(100, &mut interrupts);
+
+    let line = &ppu.framebuffer()[..SCREEN_WIDTH];
+    assert_eq!(line[0], BLACK);
+    assert_eq!(line[152], WHITE);
+}
+
+#[test]
+fn renderer_switch_waits_for_the_next_line() {
+    for (from, to, length) in [
+        (Renderer::Scanline, Renderer::Fifo, 172),
+        (Renderer::Fifo, Renderer::Scanline, 175),
+    ] {
+        let mut ppu = setup_ppu(from);
+        ppu.scx = 3;
+        let mut interrupts = InterruptController::new();
+        ppu.write_register(0xFF40, ppu.lcdc | 0x80, &mut interrupts);
+        ppu.tick(80 + 50, &mut interrupts);
+
+        ppu.set_renderer(to);
+        let mut dots = 50;
+        while ppu.mode == PpuMode::PixelTransfer {
+            ppu.tick(1, &mut interrupts);
+            dots += 1;
+        }
+        assert_eq!(dots, length);
+
+        // The next line uses the new renderer
+        ppu.tick(456 - 80 - length, &mut interrupts);
+        ppu.tick(80, &mut interrupts);
+        let mut dots = 0;
+        while ppu.mode == PpuMode::PixelTransfer {
+            ppu.tick(1, &mut interrupts);
+            dots += 1;
+        }
+        assert_eq!(dots, if to == Renderer::Fifo { 175 } else { 172 });
+    }
+}
@@ -0,0 +1,4 @@
+mod cgb;
+mod fifo;
+mod modes;
+mod render;
@@ -0,0 +1,126 @@
+use crate::interrupts::{Interrupt, InterruptController};
+use crate::ppu::{ColorMode, Ppu, PpuMode};
+
+fn setup_ppu() -> Ppu {
+    let mut ppu = Ppu::new(ColorMode::Dmg);
+    ppu.write_register(0xFF40, 0x91, &mut InterruptController::new());
+    ppu
+}
+
+#[test]
+fn lcd_off_holds_ly_at_zero() {
+    let mut ppu = Ppu::new(ColorMode::Dmg);
+
+    ppu.tick(456 * 10, &mut InterruptController::new());
+
+    assert_eq!(ppu.read_register(0xFF44), 0);
+    assert_eq!(ppu.read_register(0xFF41) & 0x03, 0);
+}
+
+#[test]
+fn scanline_mode_sequence() {
+    let mut ppu = setup_ppu();
+    assert_eq!(ppu.mode, PpuMode::OamScan);
+
+    ppu.tick(80, &mut InterruptController::new());
+    assert_eq!(ppu.mode, PpuMode::PixelTransfer);
+
+    ppu.tick(172, &mut InterruptController::new());
+    assert_eq!(ppu.mode, PpuMode::HBlank);
+
+    ppu.tick(204, &mut InterruptController::new());
+    assert_eq!(ppu.mode, PpuMode::OamScan);
+    assert_eq!(ppu.read_register(0xFF44), 1);
+}
+
+#[test]
+fn vblank_requested_at_line_144() {
+    let mut ppu = setup_ppu();
+
+    let mut interrupts = InterruptController::new();
+    ppu.tick(456 * 144, &mut interrupts);
+
+    assert_eq!(
+        interrupts.flag & Interrupt::VBlank.bit(),
+        Interrupt::VBlank.bit()
+    );
+    assert_eq!(ppu.mode, PpuMode::VBlank);
+    assert_eq!(ppu.read_register(0xFF44), 144);
+    assert!(ppu.take_frame_ready());
+    assert!(!ppu.take_frame_ready());
+}
+
+#[test]
+fn frame_wraps_after_154_lines() {
+    let mut ppu = setup_ppu();
+
+    ppu.tick(456 * 154, &mut InterruptController::new());
+
+    assert_eq!(ppu.read_register(0xFF44), 0);
+    assert_eq!(ppu.mode, PpuMode::OamScan);
+}
+
+#[test]
+fn lyc_coincidence_sets_stat_and_interrupt() {
+    let mut ppu = setup_ppu();
+    ppu.write_register(0xFF45, 2, &mut InterruptController::new());
+    ppu.write_register(0xFF41, 0x40, &mut InterruptController::new());
+
+    let mut interrupts = InterruptController::new();
+    ppu.tick(456 * 2, &mut interrupts);
+
+    assert_eq!(
+        interrupts.flag & Interrupt::Stat.bit(),
+        Interrupt::Stat.bit()
+    );
+    assert_eq!(ppu.read_register(0xFF41) & 0x04, 0x04);
+}
+
+#[test]
+fn stat_low_bits_are_read_only() {
+    let mut ppu = setup_ppu();
+
+    ppu.write_register(0xFF41, 0xFF, &mut InterruptController::new());
+
+    assert_eq!(ppu.read_register(0xFF41), 0x80 | 0x78 | 0x04 | 0x02);
+}
+
+#[test]
+fn stat_interrupt_only_on_rising_edge() {
+    let mut ppu = setup_ppu();
+    let mut interrupts = InterruptController::new();
+
+    // LY=LYC is already high on line 0, so HBlank doesn't raise the line again
+    ppu.write_register(0xFF41, 0x48, &mut interrupts);
+    assert_eq!(
+        interrupts.flag & Interrupt::Stat.bit(),
+        Interrupt::Stat.bit()
+    );
+
+    interrupts.flag = 0;
+    ppu.tick(80 + 172, &mut interrupts);
+    assert_eq!(ppu.mode, PpuMode::HBlank);
+    assert_eq!(interrupts.flag & Interrupt::Stat.bit(), 0);
+
+    // Line 1 drops LY=LYC for mode 2, so the next HBlank is a new edge
+    ppu.tick(204 + 80 + 172, &mut interrupts);
+    assert_eq!(
+        interrupts.flag & Interrupt::Stat.bit(),
+        Interrupt::Stat.bit()
+    );
+}
+
+#[test]
+fn stat_write_bug_fires_in_hblank_on_dmg_only() {
+    for (color_mode, expected) in [(ColorMode::Dmg, Interrupt::Stat.bit()), (ColorMode::Cgb, 0)] {
+        let mut ppu = Ppu::new(color_mode);
+        ppu.write_register(0xFF45, 0x10, &mut InterruptController::new());
+        ppu.write_register(0xFF40, 0x91, &mut InterruptController::new());
+        ppu.tick(80 + 172, &mut InterruptController::new());
+
+        let mut interrupts = InterruptController::new();
+        ppu.write_register(0xFF41, 0x00, &mut interrupts);
+
+        assert_eq!(interrupts.flag & Interrupt::Stat.bit(), expected);
+    }
+}
@@ -0,0 +1,93 @@
+use crate::interrupts::InterruptController;
+use crate::ppu::{ColorMode, Ppu, SCREEN_WIDTH};
+
+const WHITE: u32 = 0xFFFFFFFF;
+const BLACK: u32 = 0xFF000000;
+
+fn setup_ppu() -> Ppu {
+    let mut ppu = Ppu::new(ColorMode::Dmg);
+    ppu.write_register(0xFF47, 0xE4, &mut InterruptController::new());
+    ppu.write_register(0xFF48, 0xE4, &mut InterruptController::new());
+
+    // Tile 1: solid colour 3
+    for byte in ppu.vram[16..32].iter_mut() {
+        *byte = 0xFF;
+    }
+    ppu
+}
+
+fn render_line(ppu: &mut Ppu) {
+    ppu.write_register(0xFF40, ppu.lcdc | 0x80, &mut InterruptController::new());
+    ppu.tick(80 + 172, &mut InterruptController::new());
+}
+
+#[test]
+fn background_uses_tile_map_and_palette() {
+    let mut ppu = setup_ppu();
+    ppu.vram[0x1800 + 1] = 1;
+    ppu.lcdc = 0x11;
+
+    render_line(&mut ppu);
+
+    assert_eq!(ppu.framebuffer()[0], WHITE);
+    assert_eq!(ppu.framebuffer()[8], BLACK);
+    assert_eq!(ppu.framebuffer()[16], WHITE);
+}
+
+#[test]
+fn background_scroll_x() {
+    let mut ppu = setup_ppu();
+    ppu.vram[0x1800 + 1] = 1;
+    ppu.lcdc = 0x11;
+    ppu.scx = 4;
+
+    render_line(&mut ppu);
+
+    assert_eq!(ppu.framebuffer()[3], WHITE);
+    assert_eq!(ppu.framebuffer()[4], BLACK);
+    assert_eq!(ppu.framebuffer()[12], WHITE);
+}
+
+#[test]
+fn window_covers_background() {
+    let mut ppu = setup_ppu();
+    ppu.vram[0x1C00] = 1;
+    ppu.lcdc = 0x71;
+    ppu.wx = 7 + 80;
+
+    render_line(&mut ppu);
+
+    assert_eq!(ppu.framebuffer()[79], WHITE);
+    assert_eq!(ppu.framebuffer()[80], BLACK);
+    assert_eq!(ppu.window_line, 1);
+}
+
+#[test]
+fn sprite_drawn_over_background() {
+    let mut ppu = setup_ppu();
+    ppu.lcdc = 0x13;
+    ppu.oam[0..4].copy_from_slice(&[16, 8 + 20, 1, 0]);
+
+    render_line(&mut ppu);
+
+    assert_eq!(ppu.framebuffer()[19], WHITE);
+    assert_eq!(ppu.framebuffer()[20], BLACK);
+    assert_eq!(ppu.framebuffer()[27], BLACK);
+    assert_eq!(ppu.framebuffer()[28], WHITE);
+}
+
+#[test]
+fn only_ten_sprites_per_line() {
+    let mut ppu = setup_ppu();
+    ppu.lcdc = 0x13;
+    for i in 0..12 {
+        let x = 8 + i as u8 * 8;
+        ppu.oam[i * 4..i * 4 + 4].copy_from_slice(&[16, x, 1, 0]);
+    }
+
+    render_line(&mut ppu);
+
+    let line = &ppu.framebuffer()[..SCREEN_WIDTH];
+    assert_eq!(line[79], BLACK);
+    assert_eq!(line[80], WHITE);
+}
@@ -0,0 +1,48 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Something plugged into the link port.
+pub trait SerialDevice {
+    /// This side is driving the clock: send `byte` and return the byte shifted back in.
+    fn transfer(&mut self, byte: u8) -> u8;
+
+    /// This side is waiting on an external clock with `byte` in SB. Returns the
+    /// byte received once the other end has clocked a transfer.
+    fn receive(&mut self, _byte: u8) -> Option<u8> {
+        None
+    }
+}
+
+/// Nothing connected, the data line is pulled high.
+pub struct Disconnected;
+
+impl SerialDevice for Disconnected {
+    fn transfer(&mut self, _byte: u8) -> u8 {
+        0xFF
+    }
+}
+
+/// Records every byte sent, e.g. the results printed by test ROMs.
+pub struct Capture {
+    output: Rc<RefCell<Vec<u8>>>,
+}
+
+impl Capture {
+    pub fn new() -> Self {
+        Self {
+            output: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// Shared handle to the captured bytes, still readable once the device is connected.
+    pub fn output(&self) -> Rc<RefCell<Vec<u8>>> {
+        Rc::clone(&self.output)
+    }
+}
+
+impl SerialDevice for Capture {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        self.output.borrow_mut().push(byte);
+        0xFF
+    }
+}
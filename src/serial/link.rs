@@ -0,0 +1,60 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::serial::device::SerialDevice;
+
+#[derive(Default)]
+struct LinkState {
+    // Byte each end has in SB while waiting for the other end's clock
+    waiting: [Option<u8>; 2],
+
+    // Byte clocked into each end by the other end
+    delivered: [Option<u8>; 2],
+}
+
+/// One end of a link cable between two emulated Game Boys.
+pub struct LinkPort {
+    state: Rc<RefCell<LinkState>>,
+    end: usize,
+}
+
+/// Create both ends of a link cable.
+pub fn link_cable() -> (LinkPort, LinkPort) {
+    let state = Rc::new(RefCell::new(LinkState::default()));
+
+    (
+        LinkPort {
+            state: Rc::clone(&state),
+            end: 0,
+        },
+        LinkPort { state, end: 1 },
+    )
+}
+
+impl SerialDevice for LinkPort {
+    fn transfer(&mut self, byte: u8) -> u8 {
+        let mut state = self.state.borrow_mut();
+        let peer = 1 - self.end;
+
+        match state.waiting[peer].take() {
+            Some(reply) => {
+                state.delivered[peer] = Some(byte);
+                reply
+            }
+            // The other end isn't listening
+            None => 0xFF,
+        }
+    }
+
+    fn receive(&mut self, byte: u8) -> Option<u8> {
+        let mut state = self.state.borrow_mut();
+
+        match state.delivered[self.end].take() {
+            Some(received) => Some(received),
+            None => {
+                state.waiting[self.end] = Some(byte);
+                None
+            }
+        }
+    }
+}
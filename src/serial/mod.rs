@@ -0,0 +1,112 @@
+//0xFF01 SB  Serial transfer data, shifted out MSB first while bits are shifted in
+//0xFF02 SC  Serial control
+//Bit 7  Transfer enable (1 = start / in progress)
+//Bit 0  Clock select    (0 = external, 1 = internal 8192 Hz)
+
+pub mod device;
+pub mod link;
+
+use crate::interrupts::{Interrupt, InterruptController};
+use crate::serial::device::{Disconnected, SerialDevice};
+
+// 8192 Hz internal clock
+const CYCLES_PER_BIT: u32 = 128;
+
+pub struct Serial {
+    data: u8,
+    control: u8,
+
+    // Byte being shifted in, and how many bits of the transfer remain
+    incoming: u8,
+    bits_left: u8,
+    cycles: u32,
+
+    device: Box<dyn SerialDevice>,
+}
+
+impl Serial {
+    pub fn new() -> Self {
+        Self {
+            data: 0x00,
+            control: 0x00,
+            incoming: 0xFF,
+            bits_left: 0,
+            cycles: 0,
+            device: Box::new(Disconnected),
+        }
+    }
+
+    /// Plug `device` into the link port, replacing whatever was there.
+    pub fn connect(&mut self, device: Box<dyn SerialDevice>) {
+        self.device = device;
+    }
+
+    pub fn read_register(&self, address: u16) -> u8 {
+        match address {
+            0xFF01 => self.data,
+            0xFF02 => 0x7E | self.control,
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0xFF01 => self.data = value,
+            0xFF02 => {
+                self.control = value & 0x81;
+
+                if self.transferring() && self.internal_clock() {
+                    self.incoming = self.device.transfer(self.data);
+                    self.bits_left = 8;
+                    self.cycles = 0;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Advance by `cycles` machine cycles.
+    pub fn tick(&mut self, cycles: u32, interrupts: &mut InterruptController) {
+        if !self.transferring() {
+            return;
+        }
+
+        if !self.internal_clock() {
+            // The other end drives the clock, so the whole byte arrives at once
+            if let Some(byte) = self.device.receive(self.data) {
+                self.data = byte;
+                self.finish(interrupts);
+            }
+            return;
+        }
+
+        self.cycles += cycles;
+        while self.cycles >= CYCLES_PER_BIT && self.bits_left > 0 {
+            self.cycles -= CYCLES_PER_BIT;
+
+            self.data = self.data << 1 | self.incoming >> 7;
+            self.incoming <<= 1;
+            self.bits_left -= 1;
+
+            if self.bits_left == 0 {
+                self.finish(interrupts);
+            }
+        }
+    }
+
+    fn transferring(&self) -> bool {
+        self.control & 0x80 != 0
+    }
+
+    fn internal_clock(&self) -> bool {
+        self.control & 0x01 != 0
+    }
+
+    fn finish(&mut self, interrupts: &mut InterruptController) {
+        self.control &= 0x7F;
+        interrupts.request(Interrupt::Serial);
+    }
+}
+
+#[cfg(test)]
+mod tests;
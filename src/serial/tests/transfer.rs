@@ -0,0 +1,79 @@
+use crate::interrupts::{Interrupt, InterruptController};
+use crate::serial::device::Capture;
+use crate::serial::link::link_cable;
+use crate::serial::Serial;
+
+fn start_transfer(serial: &mut Serial, byte: u8, control: u8) {
+    serial.write_register(0xFF01, byte);
+    serial.write_register(0xFF02, control);
+}
+
+#[test]
+fn disconnected_transfer_shifts_in_ones() {
+    let mut serial = Serial::new();
+    let mut interrupts = InterruptController::new();
+
+    start_transfer(&mut serial, 0x00, 0x81);
+    serial.tick(128 * 4, &mut interrupts);
+
+    assert_eq!(serial.read_register(0xFF01), 0x0F);
+    assert_eq!(serial.read_register(0xFF02), 0xFF);
+    assert_eq!(interrupts.flag & Interrupt::Serial.bit(), 0);
+
+    serial.tick(128 * 4, &mut interrupts);
+
+    assert_eq!(serial.read_register(0xFF01), 0xFF);
+    assert_eq!(serial.read_register(0xFF02), 0x7F);
+    assert_ne!(interrupts.flag & Interrupt::Serial.bit(), 0);
+}
+
+#[test]
+fn capture_records_sent_bytes() {
+    let mut serial = Serial::new();
+    let mut interrupts = InterruptController::new();
+    let capture = Capture::new();
+    let output = capture.output();
+    serial.connect(Box::new(capture));
+
+    for &byte in b"Passed" {
+        start_transfer(&mut serial, byte, 0x81);
+        serial.tick(128 * 8, &mut interrupts);
+    }
+
+    assert_eq!(output.borrow().as_slice(), b"Passed");
+}
+
+#[test]
+fn external_clock_waits_for_other_end() {
+    let mut serial = Serial::new();
+    let mut interrupts = InterruptController::new();
+
+    start_transfer(&mut serial, 0x42, 0x80);
+    serial.tick(128 * 16, &mut interrupts);
+
+    assert_eq!(serial.read_register(0xFF02), 0xFE);
+    assert_eq!(interrupts.flag & Interrupt::Serial.bit(), 0);
+}
+
+#[test]
+fn link_cable_exchanges_bytes() {
+    let (port_a, port_b) = link_cable();
+    let mut master = Serial::new();
+    let mut slave = Serial::new();
+    let mut master_interrupts = InterruptController::new();
+    let mut slave_interrupts = InterruptController::new();
+    master.connect(Box::new(port_a));
+    slave.connect(Box::new(port_b));
+
+    start_transfer(&mut slave, 0x5A, 0x80);
+    slave.tick(1, &mut slave_interrupts);
+
+    start_transfer(&mut master, 0xC3, 0x81);
+    master.tick(128 * 8, &mut master_interrupts);
+    slave.tick(1, &mut slave_interrupts);
+
+    assert_eq!(master.read_register(0xFF01), 0x5A);
+    assert_eq!(slave.read_register(0xFF01), 0xC3);
+    assert_ne!(master_interrupts.flag & Interrupt::Serial.bit(), 0);
+    assert_ne!(slave_interrupts.flag & Interrupt::Serial.bit(), 0);
+}
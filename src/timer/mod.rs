@@ -0,0 +1,162 @@
+//0xFF04 DIV   Upper 8 bits of the 16-bit internal divider, any write resets it
+//0xFF05 TIMA  Timer counter
+//0xFF06 TMA   Timer modulo, loaded into TIMA on overflow
+//0xFF07 TAC   Timer control (bit 2 enable, bits 0-1 clock select)
+
+use crate::interrupts::{Interrupt, InterruptController};
+
+// The APU frame sequencer runs at 512 Hz off DIV bit 4, or bit 5 in double speed
+const FRAME_SEQUENCER_BIT: u16 = 1 << 12;
+const DOUBLE_SPEED_FRAME_SEQUENCER_BIT: u16 = 1 << 13;
+
+pub struct Timer {
+    counter: u16,
+    tima: u8,
+    tma: u8,
+    tac: u8,
+
+    // TIMA overflowed last cycle and reads 0 until it is reloaded from TMA
+    overflow_pending: bool,
+
+    // TIMA was reloaded from TMA this cycle
+    reloading: bool,
+
+    // Falling edges of DIV bit 4 (counter bit 12) not yet passed on to the APU
+    frame_sequencer_clocks: u32,
+    frame_sequencer_bit: u16,
+}
+
+impl Timer {
+    pub fn new() -> Self {
+        Self {
+            counter: 0,
+            tima: 0,
+            tma: 0,
+            tac: 0,
+            overflow_pending: false,
+            reloading: false,
+            frame_sequencer_clocks: 0,
+            frame_sequencer_bit: FRAME_SEQUENCER_BIT,
+        }
+    }
+
+    pub fn read_register(&self, address: u16) -> u8 {
+        match address {
+            0xFF04 => (self.counter >> 8) as u8,
+            0xFF05 => self.tima,
+            0xFF06 => self.tma,
+            0xFF07 => 0xF8 | self.tac,
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write_register(&mut self, address: u16, value: u8) {
+        match address {
+            0xFF04 => self.reset_div(),
+            // Writes in the cycle TIMA is reloaded from TMA are ignored
+            0xFF05 if !self.reloading => {
+                self.tima = value;
+                // Writing during the overflow delay cancels the reload and interrupt
+                self.overflow_pending = false;
+            }
+            0xFF06 => {
+                self.tma = value;
+                if self.reloading {
+                    self.tima = value;
+                }
+            }
+            0xFF07 => {
+                let was_high = self.timer_signal();
+                self.tac = value & 0x07;
+
+                if was_high && !self.timer_signal() {
+                    self.increment_tima();
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Clear the internal divider. Can glitch-increment TIMA if the selected bit was high.
+    pub fn reset_div(&mut self) {
+        let was_high = self.timer_signal();
+
+        if self.counter & self.frame_sequencer_bit != 0 {
+            self.frame_sequencer_clocks += 1;
+        }
+        self.counter = 0;
+
+        if was_high {
+            self.increment_tima();
+        }
+    }
+
+    /// Set the internal divider directly, for starting without a boot ROM.
+    pub fn set_counter(&mut self, counter: u16) {
+        self.counter = counter;
+    }
+
+    /// The timer runs at the CPU clock, so in double speed the APU is clocked from a higher DIV bit.
+    pub fn set_double_speed(&mut self, double_speed: bool) {
+        self.frame_sequencer_bit = if double_speed {
+            DOUBLE_SPEED_FRAME_SEQUENCER_BIT
+        } else {
+            FRAME_SEQUENCER_BIT
+        };
+    }
+
+    /// Number of APU frame sequencer clocks since the last call.
+    pub fn take_frame_sequencer_clocks(&mut self) -> u32 {
+        std::mem::take(&mut self.frame_sequencer_clocks)
+    }
+
+    /// Advance the timer by `cycles` machine cycles.
+    pub fn tick(&mut self, cycles: u32, interrupts: &mut InterruptController) {
+        for _ in 0..cycles {
+            self.reloading = false;
+
+            if self.overflow_pending {
+                self.overflow_pending = false;
+                self.reloading = true;
+                self.tima = self.tma;
+                interrupts.request(Interrupt::Timer);
+            }
+
+            let was_high = self.timer_signal();
+            let old_counter = self.counter;
+            self.counter = self.counter.wrapping_add(4);
+
+            if old_counter & !self.counter & self.frame_sequencer_bit != 0 {
+                self.frame_sequencer_clocks += 1;
+            }
+
+            if was_high && !self.timer_signal() {
+                self.increment_tima();
+            }
+        }
+    }
+
+    // TIMA is clocked by a falling edge of the selected divider bit ANDed with the enable bit
+    fn timer_signal(&self) -> bool {
+        let bit = match self.tac & 0x03 {
+            0x00 => 9,
+            0x01 => 3,
+            0x02 => 5,
+            _ => 7,
+        };
+
+        self.tac & 0x04 != 0 && (self.counter >> bit) & 1 != 0
+    }
+
+    fn increment_tima(&mut self) {
+        let (result, overflow) = self.tima.overflowing_add(1);
+        self.tima = result;
+
+        if overflow {
+            self.overflow_pending = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,131 @@
+use crate::interrupts::{Interrupt, InterruptController};
+use crate::timer::Timer;
+
+fn setup_timer(tac: u8) -> (Timer, InterruptController) {
+    let mut timer = Timer::new();
+    timer.write_register(0xFF07, tac);
+    (timer, InterruptController::new())
+}
+
+#[test]
+fn div_increments_every_64_cycles() {
+    let (mut timer, mut interrupts) = setup_timer(0x00);
+
+    timer.tick(63, &mut interrupts);
+    assert_eq!(timer.read_register(0xFF04), 0);
+
+    timer.tick(1, &mut interrupts);
+    assert_eq!(timer.read_register(0xFF04), 1);
+}
+
+#[test]
+fn tima_follows_selected_clock() {
+    let (mut timer, mut interrupts) = setup_timer(0x05);
+
+    timer.tick(16, &mut interrupts);
+    assert_eq!(timer.read_register(0xFF05), 4);
+
+    timer.write_register(0xFF07, 0x04);
+    timer.tick(239, &mut interrupts);
+    assert_eq!(timer.read_register(0xFF05), 4);
+
+    timer.tick(1, &mut interrupts);
+    assert_eq!(timer.read_register(0xFF05), 5);
+}
+
+#[test]
+fn disabled_timer_does_not_count() {
+    let (mut timer, mut interrupts) = setup_timer(0x01);
+
+    timer.tick(1000, &mut interrupts);
+
+    assert_eq!(timer.read_register(0xFF05), 0);
+    assert_eq!(timer.read_register(0xFF07), 0xF9);
+}
+
+#[test]
+fn overflow_reloads_tma_one_cycle_later() {
+    let (mut timer, mut interrupts) = setup_timer(0x05);
+    timer.write_register(0xFF05, 0xFF);
+    timer.write_register(0xFF06, 0x23);
+
+    timer.tick(4, &mut interrupts);
+    assert_eq!(timer.read_register(0xFF05), 0x00);
+    assert_eq!(interrupts.flag, 0);
+
+    timer.tick(1, &mut interrupts);
+    assert_eq!(timer.read_register(0xFF05), 0x23);
+    assert_eq!(interrupts.flag, Interrupt::Timer.bit());
+}
+
+#[test]
+fn tima_write_during_overflow_cancels_reload() {
+    let (mut timer, mut interrupts) = setup_timer(0x05);
+    timer.write_register(0xFF05, 0xFF);
+    timer.write_register(0xFF06, 0x23);
+
+    timer.tick(4, &mut interrupts);
+    timer.write_register(0xFF05, 0x10);
+    timer.tick(1, &mut interrupts);
+
+    assert_eq!(timer.read_register(0xFF05), 0x10);
+    assert_eq!(interrupts.flag, 0);
+}
+
+#[test]
+fn tima_write_during_reload_is_ignored() {
+    let (mut timer, mut interrupts) = setup_timer(0x05);
+    timer.write_register(0xFF05, 0xFF);
+    timer.write_register(0xFF06, 0x23);
+
+    timer.tick(5, &mut interrupts);
+    timer.write_register(0xFF05, 0x10);
+
+    assert_eq!(timer.read_register(0xFF05), 0x23);
+}
+
+#[test]
+fn div_write_glitches_tima_on_falling_edge() {
+    let (mut timer, mut interrupts) = setup_timer(0x05);
+
+    // Bit 3 of the divider is now high
+    timer.tick(2, &mut interrupts);
+    timer.write_register(0xFF04, 0x00);
+
+    assert_eq!(timer.read_register(0xFF05), 1);
+    assert_eq!(timer.read_register(0xFF04), 0);
+}
+
+#[test]
+fn tac_disable_glitches_tima_on_falling_edge() {
+    let (mut timer, mut interrupts) = setup_timer(0x05);
+
+    timer.tick(2, &mut interrupts);
+    timer.write_register(0xFF07, 0x01);
+
+    assert_eq!(timer.read_register(0xFF05), 1);
+}
+
+#[test]
+fn frame_sequencer_clocked_at_512_hz() {
+    let (mut timer, mut interrupts) = setup_timer(0x00);
+
+    timer.tick(2047, &mut interrupts);
+    assert_eq!(timer.take_frame_sequencer_clocks(), 0);
+
+    timer.tick(1, &mut interrupts);
+    assert_eq!(timer.take_frame_sequencer_clocks(), 1);
+
+    timer.tick(2048 * 4, &mut interrupts);
+    assert_eq!(timer.take_frame_sequencer_clocks(), 4);
+}
+
+#[test]
+fn div_reset_clocks_frame_sequencer_when_bit_high() {
+    let (mut timer, mut interrupts) = setup_timer(0x00);
+
+    timer.tick(1024 + 1, &mut interrupts);
+    timer.write_register(0xFF04, 0x00);
+
+    assert_eq!(timer.take_frame_sequencer_clocks(), 1);
+}